@@ -8,6 +8,7 @@ use graphics::*;
 use graphics::controller::*;
 use nalgebra;
 use nalgebra::PerspectiveMatrix3;
+use svo::{MaterialRegistry, VoxelData, VoxelCoord, LeafData};
 
 use errors::*;
 pub struct Config {
@@ -22,22 +23,29 @@ type F = gfx_device_gl::Factory;
 
 pub struct App {
     bundle: Bundle<R, pipe::Data<R>>,
+    // Draws the transparent instances after the bundle's opaque ones, testing depth without writing it.
+    transparent_pso: gfx::PipelineState<R, pipe::Meta>,
+    transparent_slice: gfx::Slice<R>,
     mapping: gfx::mapping::RWable<R, Instance>,
     svo_controller: SvoController,
+    materials: MaterialRegistry,
     encoder: gfx::Encoder<R, C>,
     camera_controller: CameraController,
     proj: nalgebra::Matrix4<f32>,
+    window_size: (u32, u32),
     keys_down_controller: KeysDownController,
     mouse_position_controller: MousePositionController,
     dt_controller: DtController,
 }
 
 const MAX_INSTANCE_COUNT: u32 = 2048;
+const MATERIALS_PATH: &'static str = "src/data/materials.txt";
 
 pub struct Init {
     pub color: gfx::handle::RenderTargetView<R, ColorFormat>,
     pub depth: gfx::handle::DepthStencilView<R, DepthFormat>,
     pub aspect_ratio: f32,
+    pub window_size: (u32, u32),
 }
 
 impl App {
@@ -59,6 +67,7 @@ impl App {
             color: main_color,
             depth: main_depth,
             aspect_ratio: width as f32 / height as f32,
+            window_size: (width, height),
         };
 
         let mut app = Self::new(factory, init);
@@ -80,8 +89,12 @@ impl App {
                 Resized(width, height) => {
                     let new_aspect_ratio = width as f32 / height as f32;
                     self.proj = PerspectiveMatrix3::<f32>::new(new_aspect_ratio, 45.0f32.to_radians(), 1.0, 100.0).to_matrix();
+                    self.window_size = (width, height);
                 },
                 KeyboardInput(element_state, _, Some(key_code)) => {
+                    if element_state == glutin::ElementState::Pressed {
+                        self.key_pressed(key_code);
+                    }
                     self.keys_down_controller.update(element_state, key_code);
                 },
                 MouseMoved(x, y) => {
@@ -94,6 +107,7 @@ impl App {
                 },
                 MouseInput(press_state, glutin::MouseButton::Left) => 
                     self.mouse_position_controller.update_drag_position_mut(press_state),
+                MouseInput(glutin::ElementState::Pressed, glutin::MouseButton::Right) => self.edit_under_mouse(),
                 _ => {}
             }
         }
@@ -106,6 +120,61 @@ impl App {
         device.cleanup();
     }}
 
+    fn key_pressed(&mut self, key_code: glutin::VirtualKeyCode) {
+        use glutin::VirtualKeyCode::*;
+        let keys_down = &self.keys_down_controller.set;
+        let ctrl = keys_down.contains(&LControl.into()) || keys_down.contains(&RControl.into());
+        match key_code {
+            F3 => info!("SVO stats: {}", self.svo_controller.svo.svo().stats()),
+            Z if ctrl => if !self.svo_controller.undo() { info!("Nothing to undo") },
+            Y if ctrl => if !self.svo_controller.redo() { info!("Nothing to redo") },
+            _ => {}
+        }
+    }
+
+    // Right click removes the block under the mouse, and Shift+right click puts a copy of it in front.
+    fn edit_under_mouse(&mut self) {
+        use glutin::VirtualKeyCode::{LShift, RShift};
+        let keys_down = &self.keys_down_controller.set;
+        let place = keys_down.contains(&LShift.into()) || keys_down.contains(&RShift.into());
+        let (origin, dir) = match self.mouse_ray() {
+            Some(ray) => ray,
+            None => return,
+        };
+        let (hit, in_front) = match self.svo_controller.pick(&self.materials, origin, dir) {
+            Some(picked) => picked,
+            None => return,
+        };
+        let recorded = if place {
+            let data = VoxelCoord::from_index(&hit).and_then(|coord| self.svo_controller.svo.svo().get_block(coord));
+            match (data, in_front) {
+                (Some(data), Some(index)) => self.svo_controller.set_block(&index, data),
+                _ => return,
+            }
+        } else {
+            self.svo_controller.set_block(&hit, VoxelData::empty())
+        };
+        if !recorded { warn!("That edit was too big to undo") }
+    }
+
+    // The ray through the mouse cursor, in the SVO's unit cube.
+    fn mouse_ray(&self) -> Option<(nalgebra::Vector3<f32>, nalgebra::Vector3<f32>)> {
+        use nalgebra::{Inverse, Vector3, Vector4};
+        let inverse = get!((self.proj * self.camera_controller.camera.view()).inverse());
+        let (x, y) = self.mouse_position_controller.current_mouse_position;
+        let (width, height) = self.window_size;
+        let ndc_x = 2. * x as f32 / width as f32 - 1.;
+        let ndc_y = 1. - 2. * y as f32 / height as f32;
+        // Points on the near and far planes, scaled down from the size the SVO is drawn at.
+        let scale = f32::powi(2.0, self.svo_controller.max_height);
+        let unproject = |ndc_z: f32| {
+            let point = inverse * Vector4::new(ndc_x, ndc_y, ndc_z, 1.0);
+            Vector3::new(point.x, point.y, point.z) / (point.w * scale)
+        };
+        let (near, far) = (unproject(-1.), unproject(1.));
+        Some((near, far - near))
+    }
+
     fn new(mut factory: F, init: Init) -> Self {
         use gfx::traits::FactoryExt;
         use nalgebra::*;
@@ -114,19 +183,25 @@ impl App {
             factory.create_buffer_persistent_rw(MAX_INSTANCE_COUNT as usize,
                                                 gfx::buffer::Role::Vertex,
                                                 gfx::Bind::empty());
+        let materials = MaterialRegistry::load_or_default(MATERIALS_PATH);
         let svo_controller = SvoController::new();
-        let instance_count = {
+        info!("SVO stats: {}", svo_controller.svo.svo().stats());
+        let (instance_count, opaque_count) = {
             let mut instances = instance_mapping.read_write();
-            svo_controller.svo.fill_instances(&mut instances, svo_controller.max_height)
+            let instance_count = svo_controller.svo.svo().fill_instances(&materials, &mut instances, svo_controller.max_height);
+            (instance_count, svo_graphics::partition_transparent(&mut instances[..instance_count as usize]))
         };
         assert!(instance_count <= MAX_INSTANCE_COUNT);
 
         let (quad_vertices, mut slice) =
             factory.create_vertex_buffer_with_slice(&svo_graphics::CUBE_VERTS,
                                                     &svo_graphics::CUBE_INDICES[..]);
-        slice.instances = Some((instance_count, 0));
+        let mut transparent_slice = slice.clone();
+        slice.instances = Some((opaque_count, 0));
+        transparent_slice.instances = Some((instance_count - opaque_count, opaque_count));
 
-        let texels = [[0x20, 0xA0, 0xC0, 0x00]];
+        // The colour comes from each instance's material.
+        let texels = [[0xFF, 0xFF, 0xFF, 0xFF]];
         let (_, texture_view) = factory.create_texture_immutable::<gfx::format::Rgba8>(
             texture::Kind::D2(1, 1, texture::AaMode::Single), &[&texels]
             ).unwrap();
@@ -138,6 +213,11 @@ impl App {
             include_bytes!("shader/cube_150.glslf"), 
             pipe::new()
         ).unwrap();
+        let transparent_pso = factory.create_pipeline_simple(
+            include_bytes!("shader/cube_150.glslv"),
+            include_bytes!("shader/cube_150.glslf"),
+            pipe::Init { out_depth: gfx::preset::depth::LESS_EQUAL_TEST, .. pipe::new() }
+        ).unwrap();
 
         let data = pipe::Data {
             vbuf: quad_vertices,
@@ -150,8 +230,11 @@ impl App {
 
         App {
             bundle: Bundle::new(slice, pso, data),
+            transparent_pso: transparent_pso,
+            transparent_slice: transparent_slice,
             mapping: instance_mapping,
             svo_controller: svo_controller,
+            materials: materials,
             keys_down_controller: KeysDownController::new(),
             encoder: factory.create_command_buffer().into(),
             camera_controller: CameraController::new(),
@@ -162,15 +245,19 @@ impl App {
                                                  1.0,
                                                  100.0)
                       .to_matrix(),
+            window_size: init.window_size,
         }
     }
 
     fn render<D>(&mut self, device: &mut D)
             where D: gfx::Device<Resources = R, CommandBuffer = C> {
-        {
+        // The instances were filled in App::new, so they only need refilling after an edit.
+        if !self.svo_controller.svo.take_dirty().is_empty() {
             let mut instances = self.mapping.read_write();
-            let instance_count = self.svo_controller.svo.fill_instances(&mut instances, self.svo_controller.max_height);
-            self.bundle.slice.instances = Some((instance_count, 0));
+            let instance_count = self.svo_controller.svo.svo().fill_instances(&self.materials, &mut instances, self.svo_controller.max_height);
+            let opaque_count = svo_graphics::partition_transparent(&mut instances[..instance_count as usize]);
+            self.bundle.slice.instances = Some((opaque_count, 0));
+            self.transparent_slice.instances = Some((instance_count - opaque_count, opaque_count));
         }
 
         let view = self.camera_controller.camera.view();
@@ -181,6 +268,7 @@ impl App {
         self.encoder.clear_depth(&self.bundle.data.out_depth, 1.0);
         self.encoder.draw(&self.bundle.slice, &self.bundle.pso, &self.bundle.data);
         self.bundle.encode(&mut self.encoder);
+        self.encoder.draw(&self.transparent_slice, &self.transparent_pso, &self.bundle.data);
         self.encoder.flush(device);
     }
 }
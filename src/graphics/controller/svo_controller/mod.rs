@@ -0,0 +1,59 @@
+use nalgebra::{Vector3, Norm};
+use svo::{SVO, EditHistory, TrackedSVO, VoxelData, VoxelCoord, MaterialRegistry};
+
+#[cfg(test)]
+mod test;
+
+// Enough to undo a good few thousand single block edits.
+const HISTORY_BYTES: usize = 16 * 1024 * 1024;
+
+pub struct SvoController {
+    pub svo: TrackedSVO,
+    pub max_height: i32,
+    pub history: EditHistory,
+}
+
+impl SvoController {
+    pub fn new() -> Self {
+        SvoController {
+            svo: TrackedSVO::new(SVO::example()),
+            max_height: 5,
+            history: EditHistory::new(HISTORY_BYTES),
+        }
+    }
+
+    // Returns false if the edit was too big for the history to keep, so it can't be undone.
+    pub fn set_block(&mut self, index: &[u8], new_data: VoxelData) -> bool {
+        let history = &mut self.history;
+        self.svo.edit(index, |svo| history.set_block(svo, index, new_data))
+    }
+
+    // The path to the block that a ray in the SVO's unit cube hits, and the path to the space in front
+    // of it if that's inside the SVO. Blocks are picked at max_height, where they're one unit across.
+    pub fn pick(&self, materials: &MaterialRegistry, origin: Vector3<f32>, dir: Vector3<f32>) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+        let hit = get!(self.svo.svo().cast_ray_with(materials, origin, dir));
+        let depth = self.max_height as u32;
+        let scale = (1 << depth) as f32;
+        // The hit is on the block's surface, so step a fraction of a block either side of it.
+        let nudge = dir.normalize() * (0.25 / scale);
+        let index_at = |position: Vector3<f32>| {
+            guard!((0..3).all(|axis| position[axis] >= 0. && position[axis] < 1.));
+            let coord = VoxelCoord::new((position.x * scale) as u32, (position.y * scale) as u32, (position.z * scale) as u32, depth);
+            Some(coord.to_index())
+        };
+        Some((get!(index_at(hit + nudge)), index_at(hit - nudge)))
+    }
+
+    // Undo and redo can touch anything, so they dirty the whole SVO.
+    pub fn undo(&mut self) -> bool {
+        if !self.history.can_undo() { return false; }
+        let history = &mut self.history;
+        self.svo.edit(&[], |svo| history.undo(svo))
+    }
+
+    pub fn redo(&mut self) -> bool {
+        if !self.history.can_redo() { return false; }
+        let history = &mut self.history;
+        self.svo.edit(&[], |svo| history.redo(svo))
+    }
+}
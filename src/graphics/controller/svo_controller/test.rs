@@ -0,0 +1,40 @@
+use nalgebra::Vector3;
+use svo::{SVO, TrackedSVO, VoxelData, VoxelCoord, MaterialRegistry};
+use super::SvoController;
+
+// Stone in the octants above the middle along x, and air everywhere else.
+fn half_stone() -> SvoController {
+    let mut controller = SvoController::new();
+    controller.svo = TrackedSVO::new(SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new((ix & 1) as i32))));
+    controller
+}
+
+fn index(x: u32, y: u32, z: u32) -> Vec<u8> {
+    VoxelCoord::new(x, y, z, 5).to_index()
+}
+
+#[test]
+fn pick_hit_face() {
+    let materials = MaterialRegistry::default();
+    let picked = half_stone().pick(&materials, Vector3::new(-1., 0.26, 0.3), Vector3::new(1., 0., 0.));
+    // The ray crosses the air and hits the stone's face at x = 0.5, which is block 16 of 32.
+    assert_eq!(picked, Some((index(16, 8, 9), Some(index(15, 8, 9)))));
+}
+
+#[test]
+fn pick_in_front_outside_of_the_cube() {
+    let materials = MaterialRegistry::default();
+    let controller = SvoController::new();
+    // SVO::example is solid at the origin, so the ray hits the face of the cube itself.
+    let picked = controller.pick(&materials, Vector3::new(-1., 0.1, 0.1), Vector3::new(1., 0., 0.));
+    assert_eq!(picked, Some((index(0, 3, 3), None)));
+}
+
+#[test]
+fn pick_misses() {
+    let materials = MaterialRegistry::default();
+    let controller = half_stone();
+    // Past the cube, and through air and then out of the cube's side.
+    assert_eq!(controller.pick(&materials, Vector3::new(2., 0.5, 0.5), Vector3::new(1., 0., 0.)), None);
+    assert_eq!(controller.pick(&materials, Vector3::new(0.2, -1., 0.2), Vector3::new(0., 1., 0.)), None);
+}
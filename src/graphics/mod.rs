@@ -16,6 +16,8 @@ gfx_defines!{
     vertex Instance {
         translate: [f32; 3] = "a_Translate",
         side_width: f32 = "a_SideWidth",
+        color: [f32; 4] = "a_Color",
+        emissive: f32 = "a_Emissive",
     }
 
     constant Locals {
@@ -27,7 +29,7 @@ gfx_defines!{
         instance: gfx::InstanceBuffer<Instance> = (),
         locals: gfx::ConstantBuffer<Locals> = "Locals",
         color: gfx::TextureSampler<[f32; 4]> = "t_Color",
-        out_color: gfx::RenderTarget<ColorFormat> = "Target0",
+        out_color: gfx::BlendTarget<ColorFormat> = ("Target0", gfx::state::MASK_ALL, gfx::preset::blend::ALPHA),
         out_depth: gfx::DepthTarget<DepthFormat> =
             gfx::preset::depth::LESS_EQUAL_WRITE,
     }
@@ -1,17 +1,148 @@
 use graphics::{Instance, Vertex};
 use nalgebra::Vector3;
 use svo;
-use svo::SVO;
-use std::slice::IterMut;
+use svo::{SVO, ArenaSVO, PersistentSVO, LeafData, LodView, LodPolicy, SvoVisitor, Position, Material, MaterialRegistry};
+use world::World;
+use svo::arena::Slot;
 
 #[cfg(test)]
 mod test;
 
-impl SVO {
-    pub fn fill_instances(&self, instances: &mut [Instance], max_height: i32) -> u32 {
+impl Instance {
+    pub fn new(origin: Vector3<f32>, side_width: f32, material: &Material) -> Instance {
+        Instance {
+            translate: *origin.as_ref(),
+            side_width: side_width,
+            color: material.colour,
+            emissive: if material.emissive { 1.0 } else { 0.0 },
+        }
+    }
+}
+
+// Move the instances that need blending after the opaque ones, keeping the order within each group,
+// and return how many are opaque. Transparent ones are drawn last without writing depth.
+pub fn partition_transparent(instances: &mut [Instance]) -> u32 {
+    let (opaque, transparent): (Vec<Instance>, Vec<Instance>) = instances.iter().partition(|instance| instance.color[3] >= 1.0);
+    for (slot, instance) in instances.iter_mut().zip(opaque.iter().chain(transparent.iter())) {
+        *slot = *instance;
+    }
+    opaque.len() as u32
+}
+
+impl<T: LeafData> SVO<T> {
+    pub fn fill_instances(&self, materials: &MaterialRegistry, instances: &mut [Instance], max_height: i32) -> u32 {
+        self.fill_instances_at(materials, instances, Vector3::new(0.0, 0.0, 0.0), f32::powi(2.0, max_height))
+    }
+
+    // Fill the instances for the SVO scaled up to the given size and moved to the given origin.
+    // Only leaves with visible materials get an instance.
+    pub fn fill_instances_at(&self, materials: &MaterialRegistry, instances: &mut [Instance], origin: Vector3<f32>, scale: f32) -> u32 {
+        let mut instance_count = 0;
+        for leaf in self.leaves() {
+            let material = leaf.data.material(materials);
+            if !material.is_visible() { continue; }
+            // Deliberately panic when the array is not long enough
+            // TODO: dynamically extend the array somehow?
+            instances[instance_count] = Instance::new(leaf.origin * scale + origin, leaf.side_len * scale, material);
+            instance_count += 1;
+        }
+        assert!(instance_count <= u32::max_value() as usize);
+        instance_count as u32
+    }
+}
+
+impl<T: LeafData + Send + Sync> SVO<T> {
+    // The same instances as fill_instances, in the same order, found on up to the given number of threads.
+    pub fn fill_instances_parallel(&self, materials: &MaterialRegistry, instances: &mut [Instance], max_height: i32, threads: usize) -> u32 {
+        let scale = f32::powi(2.0, max_height);
+        let found = self.filter_map_leaves(threads, |leaf| {
+            let material = leaf.data.material(materials);
+            guard!(material.is_visible());
+            Some(Instance::new(leaf.origin * scale, leaf.side_len * scale, material))
+        });
+        copy_instances(&found, instances)
+    }
+}
+
+// Deliberately panic when the array is not long enough, like SVO::fill_instances
+fn copy_instances(found: &[Instance], instances: &mut [Instance]) -> u32 {
+    instances[..found.len()].copy_from_slice(found);
+    assert!(found.len() <= u32::max_value() as usize);
+    found.len() as u32
+}
+
+impl<T: LeafData> World<T> {
+    // Chunks are filled in order of their coordinates so the output doesn't depend on the hash map.
+    pub fn fill_instances(&self, materials: &MaterialRegistry, instances: &mut [Instance]) -> u32 {
+        let mut chunks: Vec<_> = self.chunks().collect();
+        chunks.sort_by_key(|&(&chunk, _)| chunk);
+        let mut instance_count = 0;
+        for (&chunk, svo) in chunks {
+            instance_count += svo.fill_instances_at(materials,
+                                                    &mut instances[instance_count as usize..],
+                                                    self.chunk_origin(chunk),
+                                                    self.chunk_size());
+        }
+        instance_count
+    }
+}
+
+impl<'a, T: LeafData, P: LodPolicy<T>> LodView<'a, T, P> {
+    pub fn fill_instances(&self, materials: &MaterialRegistry, instances: &mut [Instance], max_height: i32) -> u32 {
+        let mut filler = InstanceFiller {
+            materials: materials,
+            instances: instances,
+            count: 0,
+            origin: Vector3::new(0.0, 0.0, 0.0),
+            scale: f32::powi(2.0, max_height),
+        };
+        self.visit(&mut filler);
+        assert!(filler.count <= u32::max_value() as usize);
+        filler.count as u32
+    }
+}
+
+impl<T: LeafData> PersistentSVO<T> {
+    // See SVO::fill_instances_at.
+    pub fn fill_instances_at(&self, materials: &MaterialRegistry, instances: &mut [Instance], origin: Vector3<f32>, scale: f32) -> u32 {
+        let mut filler = InstanceFiller {
+            materials: materials,
+            instances: instances,
+            count: 0,
+            origin: origin,
+            scale: scale,
+        };
+        self.visit(&mut filler);
+        assert!(filler.count <= u32::max_value() as usize);
+        filler.count as u32
+    }
+}
+
+struct InstanceFiller<'a> {
+    materials: &'a MaterialRegistry,
+    instances: &'a mut [Instance],
+    count: usize,
+    origin: Vector3<f32>,
+    scale: f32,
+}
+
+impl<'a, T: LeafData> SvoVisitor<T> for InstanceFiller<'a> {
+    fn visit_leaf(&mut self, position: &Position, data: &T) {
+        let material = data.material(self.materials);
+        if !material.is_visible() { return; }
+        // Deliberately panic when the array is not long enough, like SVO::fill_instances
+        self.instances[self.count] = Instance::new(position.origin * self.scale + self.origin, position.side_len * self.scale, material);
+        self.count += 1;
+    }
+}
+
+impl<T: LeafData> ArenaSVO<T> {
+    pub fn fill_instances(&self, materials: &MaterialRegistry, instances: &mut [Instance], max_height: i32) -> u32 {
         let instances_len = instances.len();
         let mut instance_iter = instances.iter_mut();
-        self.fill_instances_helper(&mut instance_iter,
+        self.fill_instances_helper(materials,
+                                   self.root(),
+                                   &mut |instance| *instance_iter.next().unwrap() = instance,
                                    Vector3::new(0.0, 0.0, 0.0),
                                    f32::powi(2.0, max_height));
         let instance_count = instances_len - instance_iter.len();
@@ -20,30 +151,59 @@ impl SVO {
     }
 
     fn fill_instances_helper(&self,
-                             instances_iter: &mut IterMut<Instance>,
+                             materials: &MaterialRegistry,
+                             slot: Slot,
+                             emit: &mut FnMut(Instance),
                              origin: Vector3<f32>,
                              side_width: f32) {
-        match self {
-            &SVO::Voxel { data } if data.voxel_type == 0 => {}
-            &SVO::Voxel { .. } => {
-                *instances_iter.next().unwrap() = Instance {
-                    // Deliberately panic when the array is not long enough
-                    translate: *origin.as_ref(), // TODO: dynamically extend the array somehow?
-                    side_width: side_width,
+        match slot {
+            Slot::Leaf(leaf) => {
+                let material = self.leaf(leaf).material(materials);
+                if material.is_visible() {
+                    emit(Instance::new(origin, side_width, material));
                 }
             }
-            &SVO::Octants(ref suboctants) => {
+            Slot::Node(node) => {
                 for i in 0..8 {
                     let new_side_width = side_width / 2.0;
-                    let offset = svo::offset_float(i as u8, new_side_width);
-                    suboctants[i]
-                        .fill_instances_helper(instances_iter, origin + offset, new_side_width);
+                    let offset = svo::offset_float(i, new_side_width);
+                    self.fill_instances_helper(materials, self.child(node, i), emit, origin + offset, new_side_width);
                 }
             }
         }
     }
 }
 
+impl<T: LeafData + Sync> ArenaSVO<T> {
+    // The same instances as fill_instances, in the same order, found on up to the given number of threads.
+    pub fn fill_instances_parallel(&self, materials: &MaterialRegistry, instances: &mut [Instance], max_height: i32, threads: usize) -> u32 {
+        let found = self.collect_instances(materials, self.root(), Vector3::new(0.0, 0.0, 0.0), f32::powi(2.0, max_height), threads);
+        copy_instances(&found, instances)
+    }
+
+    fn collect_instances(&self,
+                         materials: &MaterialRegistry,
+                         slot: Slot,
+                         origin: Vector3<f32>,
+                         side_width: f32,
+                         threads: usize) -> Vec<Instance> {
+        match slot {
+            Slot::Node(node) if threads > 1 => {
+                let new_side_width = side_width / 2.0;
+                svo::map_octants(threads, |i, octant_threads| {
+                    let offset = svo::offset_float(i, new_side_width);
+                    self.collect_instances(materials, self.child(node, i), origin + offset, new_side_width, octant_threads)
+                }).into_iter().flat_map(|instances| instances).collect()
+            }
+            _ => {
+                let mut found = Vec::new();
+                self.fill_instances_helper(materials, slot, &mut |instance| found.push(instance), origin, side_width);
+                found
+            }
+        }
+    }
+}
+
 macro_rules! vert (($p:expr, $t:expr) => (
     Vertex {
         pos: [$p[0] as f32, $p[1] as f32, $p[2] as f32],
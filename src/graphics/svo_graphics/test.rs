@@ -1,11 +1,18 @@
 use graphics::Instance;
-use svo::{SVO, VoxelData};
+use graphics::svo_graphics::partition_transparent;
+use svo::{SVO, ArenaSVO, VoxelData, AnySolid, MaterialRegistry};
+use world::World;
+
+// The colour of stone in src/data/materials.txt, 20A0C0FF.
+const STONE: [f32; 4] = [0x20 as f32 / 255., 0xA0 as f32 / 255., 0xC0 as f32 / 255., 1.];
 
 impl Instance {
     fn zero() -> Instance {
         Instance {
             translate: [0.0, 0.0, 0.0],
             side_width: 0.0,
+            color: [0.0, 0.0, 0.0, 0.0],
+            emissive: 0.0,
         }
     }
 }
@@ -14,11 +21,13 @@ impl Instance {
 fn voxel_instance() {
     let svo = SVO::new_voxel(VoxelData::new(1));
     let mut instances = vec![Instance::zero()];
-    let count = svo.fill_instances(&mut instances, 2);
+    let count = svo.fill_instances(&MaterialRegistry::default(), &mut instances, 2);
     assert_eq!(count, 1);
     let expected_instances = vec![Instance {
                                       translate: [0.0, 0.0, 0.0],
                                       side_width: 4.0,
+                                      color: STONE,
+                                      emissive: 0.0,
                                   }];
     assert_eq!(expected_instances, instances);
 }
@@ -28,17 +37,17 @@ fn octants_instance() {
     let svo = SVO::new_octants(|_| SVO::new_voxel(VoxelData::new(1)));
 
     let mut instances = vec![Instance::zero(); 8];
-    let count = svo.fill_instances(&mut instances, 2);
+    let count = svo.fill_instances(&MaterialRegistry::default(), &mut instances, 2);
     assert_eq!(count, 8);
     let expected_instances = vec![
-        Instance { translate: [0.0, 0.0, 0.0], side_width: 2.0 },
-        Instance { translate: [2.0, 0.0, 0.0], side_width: 2.0 },
-        Instance { translate: [0.0, 2.0, 0.0], side_width: 2.0 },
-        Instance { translate: [2.0, 2.0, 0.0], side_width: 2.0 },
-        Instance { translate: [0.0, 0.0, 2.0], side_width: 2.0 },
-        Instance { translate: [2.0, 0.0, 2.0], side_width: 2.0 },
-        Instance { translate: [0.0, 2.0, 2.0], side_width: 2.0 },
-        Instance { translate: [2.0, 2.0, 2.0], side_width: 2.0 },
+        Instance { translate: [0.0, 0.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 0.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 2.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 2.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 0.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 0.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 2.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 2.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
     ];
     instances.truncate(count as usize);
     assert_eq!(instances, expected_instances);
@@ -53,27 +62,27 @@ fn octants_instance_two() {
     });
     let mut instances = vec![Instance::zero(); 24];
 
-    let count = svo.fill_instances(&mut instances, 2);
+    let count = svo.fill_instances(&MaterialRegistry::default(), &mut instances, 2);
     assert_eq!(count, 15);
 
     let expected_instances = vec![
-        Instance { translate: [0.0, 0.0, 0.0], side_width: 2.0 },
-        Instance { translate: [2.0, 0.0, 0.0], side_width: 2.0 },
-        Instance { translate: [0.0, 2.0, 0.0], side_width: 2.0 },
-        Instance { translate: [2.0, 2.0, 0.0], side_width: 2.0 },
-        Instance { translate: [0.0, 0.0, 2.0], side_width: 2.0 },
-
-        Instance { translate: [2.0, 0.0, 2.0], side_width: 1.0 },
-        Instance { translate: [3.0, 0.0, 2.0], side_width: 1.0 },
-        Instance { translate: [2.0, 1.0, 2.0], side_width: 1.0 },
-        Instance { translate: [3.0, 1.0, 2.0], side_width: 1.0 },
-        Instance { translate: [2.0, 0.0, 3.0], side_width: 1.0 },
-        Instance { translate: [3.0, 0.0, 3.0], side_width: 1.0 },
-        Instance { translate: [2.0, 1.0, 3.0], side_width: 1.0 },
-        Instance { translate: [3.0, 1.0, 3.0], side_width: 1.0 },
-
-        Instance { translate: [0.0, 2.0, 2.0], side_width: 2.0 },
-        Instance { translate: [2.0, 2.0, 2.0], side_width: 2.0 },
+        Instance { translate: [0.0, 0.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 0.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 2.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 2.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 0.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+
+        Instance { translate: [2.0, 0.0, 2.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [3.0, 0.0, 2.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 1.0, 2.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [3.0, 1.0, 2.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 0.0, 3.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [3.0, 0.0, 3.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 1.0, 3.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [3.0, 1.0, 3.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+
+        Instance { translate: [0.0, 2.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [2.0, 2.0, 2.0], side_width: 2.0, color: STONE, emissive: 0.0 },
     ];
     instances.truncate(count as usize);
     assert_eq!(instances, expected_instances);
@@ -84,7 +93,7 @@ fn octants_instance_two() {
 fn panic_on_overflow() {
     let svo = SVO::new_voxel(VoxelData::new(1));
     let mut instances: [Instance; 0] = [];
-    svo.fill_instances(&mut instances, 2);
+    svo.fill_instances(&MaterialRegistry::default(), &mut instances, 2);
 }
 
 #[test]
@@ -95,17 +104,109 @@ fn octants_instance_empty() {
     });
 
     let mut instances = vec![Instance::zero(); 8];
-    let count = svo.fill_instances(&mut instances, 3);
+    let count = svo.fill_instances(&MaterialRegistry::default(), &mut instances, 3);
     assert_eq!(count, 6);
 
     let expected_instances = vec![
-        Instance { translate: [0.0, 0.0, 0.0], side_width: 4.0 },
-        Instance { translate: [0.0, 4.0, 0.0], side_width: 4.0 },
-        Instance { translate: [0.0, 0.0, 4.0], side_width: 4.0 },
-        Instance { translate: [4.0, 0.0, 4.0], side_width: 4.0 },
-        Instance { translate: [0.0, 4.0, 4.0], side_width: 4.0 },
-        Instance { translate: [4.0, 4.0, 4.0], side_width: 4.0 },
+        Instance { translate: [0.0, 0.0, 0.0], side_width: 4.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 4.0, 0.0], side_width: 4.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 0.0, 4.0], side_width: 4.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [4.0, 0.0, 4.0], side_width: 4.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [0.0, 4.0, 4.0], side_width: 4.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [4.0, 4.0, 4.0], side_width: 4.0, color: STONE, emissive: 0.0 },
     ];
     instances.truncate(count as usize);
     assert_eq!(instances, expected_instances);
 }
+
+#[test]
+fn arena_instances_match_svo() {
+    let svo = SVO::new_octants(|i| if i != 5 {
+        SVO::new_voxel(VoxelData::new((i % 2) as i32))
+    } else {
+        SVO::new_octants(|j| SVO::new_voxel(VoxelData::new((j % 3) as i32)))
+    });
+    let arena = ArenaSVO::from_svo(&svo);
+
+    let mut instances = vec![Instance::zero(); 16];
+    let count = svo.fill_instances(&MaterialRegistry::default(), &mut instances, 2);
+    let mut arena_instances = vec![Instance::zero(); 16];
+    let arena_count = arena.fill_instances(&MaterialRegistry::default(), &mut arena_instances, 2);
+    assert_eq!(count, arena_count);
+    assert_eq!(instances, arena_instances);
+}
+
+#[test]
+fn world_instances_offset_by_chunk() {
+    let mut world = World::new(4.0, 1);
+    world.set_block((-1, 0, 0), VoxelData::new(1));
+    world.set_block((3, 2, 0), VoxelData::new(2));
+
+    let mut instances = vec![Instance::zero(); 4];
+    let count = world.fill_instances(&MaterialRegistry::default(), &mut instances);
+    let expected_instances = vec![
+        Instance { translate: [-2.0, 0.0, 0.0], side_width: 2.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [6.0, 4.0, 0.0], side_width: 2.0, color: MaterialRegistry::default().get(2).colour, emissive: 0.0 },
+    ];
+    instances.truncate(count as usize);
+    assert_eq!(instances, expected_instances);
+}
+
+#[test]
+fn lod_view_instances() {
+    let svo = SVO::new_octants(|i| if i != 5 {
+        SVO::new_voxel(VoxelData::new((i % 2) as i32))
+    } else {
+        SVO::new_octants(|j| SVO::new_voxel(VoxelData::new((j % 3) as i32)))
+    });
+    let mut instances = vec![Instance::zero(); 16];
+    let count = svo.lod_view(1, &AnySolid).fill_instances(&MaterialRegistry::default(), &mut instances, 1);
+    let expected_instances = vec![
+        Instance { translate: [1.0, 0.0, 0.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [1.0, 1.0, 0.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [1.0, 0.0, 1.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+        Instance { translate: [1.0, 1.0, 1.0], side_width: 1.0, color: STONE, emissive: 0.0 },
+    ];
+    instances.truncate(count as usize);
+    assert_eq!(instances, expected_instances);
+
+    let mut copy_instances = vec![Instance::zero(); 16];
+    let copy_count = svo.lod(1, &AnySolid).fill_instances(&MaterialRegistry::default(), &mut copy_instances, 1);
+    copy_instances.truncate(copy_count as usize);
+    assert_eq!(instances, copy_instances);
+}
+
+#[test]
+fn parallel_instances_match_serial() {
+    let image: Vec<u8> = (0..64 * 64).map(|i| ((i * 37 + i / 64 * 11) % 256) as u8).collect();
+    let materials = MaterialRegistry::default();
+    let svo = SVO::height_map(4, &image, 64, 64, &materials);
+    let arena = ArenaSVO::from_svo(&svo);
+
+    let mut instances = vec![Instance::zero(); 4096];
+    let count = svo.fill_instances(&materials, &mut instances, 2);
+    for threads in 1..12 {
+        let mut parallel_instances = vec![Instance::zero(); 4096];
+        assert_eq!(svo.fill_instances_parallel(&materials, &mut parallel_instances, 2, threads), count);
+        assert_eq!(parallel_instances, instances);
+
+        let mut arena_instances = vec![Instance::zero(); 4096];
+        assert_eq!(arena.fill_instances_parallel(&materials, &mut arena_instances, 2, threads), count);
+        assert_eq!(arena_instances, instances);
+    }
+}
+
+#[test]
+fn transparent_instances_go_last() {
+    let materials = MaterialRegistry::default();
+    let (water, glass) = (materials.id_of("water").unwrap(), materials.id_of("glass").unwrap());
+    let svo = SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new(match ix { 0 => water, 3 => glass, _ => 1 })));
+
+    let mut instances = vec![Instance::zero(); 8];
+    let count = svo.fill_instances(&materials, &mut instances, 1);
+    assert_eq!(count, 8);
+    assert_eq!(partition_transparent(&mut instances), 6);
+    let translates: Vec<_> = instances.iter().map(|instance| instance.translate).collect();
+    assert_eq!(translates, vec![[1., 0., 0.], [0., 1., 0.], [0., 0., 1.], [1., 0., 1.], [0., 1., 1.], [1., 1., 1.],
+                                [0., 0., 0.], [1., 1., 0.]]);
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 // Modified by David McGillicuddy
 #![allow(dead_code)]
+#![cfg_attr(feature = "nightly", feature(test))]
 
 extern crate env_logger;
 #[macro_use]
@@ -24,11 +25,14 @@ extern crate byteorder;
 extern crate log;
 #[cfg(test)]
 extern crate quickcheck;
+#[cfg(all(feature = "nightly", test))]
+extern crate test;
 extern crate glutin;
 extern crate gfx_device_gl;
 extern crate gfx_window_glutin;
 extern crate arrayvec;
 extern crate num;
+extern crate crossbeam;
 #[macro_use]
 extern crate error_chain;
 
@@ -47,6 +51,7 @@ macro_rules! guard(
 mod app;
 mod svo;
 mod graphics;
+mod world;
 
 use app::App;
 
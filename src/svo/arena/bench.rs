@@ -0,0 +1,90 @@
+use nalgebra::Vector3;
+use svo::*;
+use test::{Bencher, black_box};
+
+const DEPTH: u32 = 6;
+const WIDTH: u32 = 64;
+
+fn terrain() -> SVO {
+    let image: Vec<u8> = (0..WIDTH * WIDTH).map(|i| {
+        let (x, y) = (i % WIDTH, i / WIDTH);
+        ((x * 7 + y * 13) % 256) as u8
+    }).collect();
+    SVO::height_map(DEPTH, &image, WIDTH, WIDTH, &MaterialRegistry::default())
+}
+
+// A fixed spread of paths to the deepest level of the tree.
+fn edit_paths() -> Vec<Vec<u8>> {
+    (0..256u32).map(|i| {
+        (0..DEPTH).map(|d| ((i * 5 + d * 3 + (i >> d)) % 8) as u8).collect()
+    }).collect()
+}
+
+fn rays() -> Vec<(Vector3<f32>, Vector3<f32>)> {
+    (0..64).map(|i| {
+        let f = i as f32 / 64.;
+        (Vector3::new(f, 2., 1. - f), Vector3::new(0.1, -1., f - 0.5))
+    }).collect()
+}
+
+#[bench]
+fn set_block_boxed(b: &mut Bencher) {
+    let paths = edit_paths();
+    let terrain = terrain();
+    b.iter(|| {
+        let mut svo = terrain.clone();
+        for (i, path) in paths.iter().enumerate() {
+            svo.set_block(path, VoxelData::new(i as i32 % 3));
+        }
+        black_box(svo)
+    });
+}
+
+#[bench]
+fn set_block_arena(b: &mut Bencher) {
+    let paths = edit_paths();
+    let svo = terrain();
+    b.iter(|| {
+        let mut arena = ArenaSVO::from_svo(&svo);
+        for (i, path) in paths.iter().enumerate() {
+            arena.set_block(path, VoxelData::new(i as i32 % 3));
+        }
+        black_box(arena)
+    });
+}
+
+#[bench]
+fn cast_ray_boxed(b: &mut Bencher) {
+    let svo = terrain();
+    let rays = rays();
+    b.iter(|| {
+        for &(origin, dir) in &rays { black_box(svo.cast_ray(origin, dir)); }
+    });
+}
+
+#[bench]
+fn cast_ray_arena(b: &mut Bencher) {
+    let arena = ArenaSVO::from_svo(&terrain());
+    let rays = rays();
+    b.iter(|| {
+        for &(origin, dir) in &rays { black_box(arena.cast_ray(origin, dir)); }
+    });
+}
+
+#[bench]
+fn fill_instances_boxed(b: &mut Bencher) {
+    use graphics::Instance;
+    let svo = terrain();
+    let materials = MaterialRegistry::default();
+    let mut instances = vec![Instance { translate: [0.0; 3], side_width: 0.0, color: [0.0; 4], emissive: 0.0 }; 1 << (3 * DEPTH)];
+    b.iter(|| black_box(svo.fill_instances(&materials, &mut instances, DEPTH as i32)));
+}
+
+#[bench]
+fn fill_instances_arena(b: &mut Bencher) {
+    use graphics::Instance;
+    let arena = ArenaSVO::from_svo(&terrain());
+    let materials = MaterialRegistry::default();
+    let mut instances = vec![Instance { translate: [0.0; 3], side_width: 0.0, color: [0.0; 4], emissive: 0.0 }; 1 << (3 * DEPTH)];
+    b.iter(|| black_box(arena.fill_instances(&materials, &mut instances, DEPTH as i32)));
+}
@@ -0,0 +1,316 @@
+use nalgebra::Vector3;
+use std::io::{Read, Write, Result, Error, ErrorKind};
+use svo::*;
+use svo::cast_ray::cast_ray_octree;
+use svo::traverse::OctreeNode;
+use svo::save_load::{VOXEL_TAG, OCTANT_TAG};
+
+#[cfg(test)]
+mod test;
+#[cfg(all(feature = "nightly", test))]
+mod bench;
+
+// Where a child lives: an index into either the leaf table or the node table.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Slot {
+    Leaf(u32),
+    Node(u32),
+}
+
+// Bit i of the leaf_mask is set if children[i] indexes the leaf table rather than the node table.
+#[derive(Debug, PartialEq, Copy, Clone)]
+struct Node {
+    leaf_mask: u8,
+    children: [u32; 8],
+}
+
+impl Node {
+    fn child(&self, ix: u8) -> Slot {
+        let child = self.children[ix as usize];
+        if self.leaf_mask & (1 << ix) != 0 { Slot::Leaf(child) } else { Slot::Node(child) }
+    }
+
+    fn set_child(&mut self, ix: u8, slot: Slot) {
+        match slot {
+            Slot::Leaf(leaf) => {
+                self.leaf_mask |= 1 << ix;
+                self.children[ix as usize] = leaf;
+            },
+            Slot::Node(node) => {
+                self.leaf_mask &= !(1 << ix);
+                self.children[ix as usize] = node;
+            }
+        }
+    }
+}
+
+// A slot in an ArenaSVO, for the walks in svo::traverse.
+struct ArenaNode<'a, T: 'a> {
+    arena: &'a ArenaSVO<T>,
+    slot: Slot,
+}
+
+impl<'a, T> Clone for ArenaNode<'a, T> {
+    fn clone(&self) -> ArenaNode<'a, T> {
+        *self
+    }
+}
+
+impl<'a, T> Copy for ArenaNode<'a, T> {}
+
+impl<'a, T: LeafData> OctreeNode<T> for ArenaNode<'a, T> {
+    fn leaf_data(&self) -> Option<T> {
+        match self.slot {
+            Slot::Leaf(leaf) => Some(self.arena.leaf(leaf)),
+            Slot::Node(_) => None,
+        }
+    }
+
+    fn child(&self, ix: u8) -> ArenaNode<'a, T> {
+        match self.slot {
+            Slot::Node(node) => ArenaNode { arena: self.arena, slot: self.arena.child(node, ix) },
+            Slot::Leaf(_) => panic!("A leaf has no children"),
+        }
+    }
+}
+
+// The same octree as an SVO, but with every node and leaf kept in a contiguous arena instead of
+// each child being boxed separately. Slots freed by edits are reused by later ones.
+#[derive(Debug, Clone)]
+pub struct ArenaSVO<T = VoxelData> {
+    root: Slot,
+    nodes: Vec<Node>,
+    leaves: Vec<T>,
+    free_nodes: Vec<u32>,
+    free_leaves: Vec<u32>,
+}
+
+impl<T: LeafData> ArenaSVO<T> {
+    pub fn new_voxel(data: T) -> ArenaSVO<T> {
+        let mut arena = ArenaSVO::with_root(Slot::Leaf(0));
+        arena.leaves.push(data);
+        arena
+    }
+
+    fn with_root(root: Slot) -> ArenaSVO<T> {
+        ArenaSVO {
+            root: root,
+            nodes: Vec::new(),
+            leaves: Vec::new(),
+            free_nodes: Vec::new(),
+            free_leaves: Vec::new(),
+        }
+    }
+
+    pub fn from_svo(svo: &SVO<T>) -> ArenaSVO<T> {
+        let mut arena = ArenaSVO::with_root(Slot::Leaf(0));
+        arena.root = arena.copy_svo(svo);
+        arena
+    }
+
+    fn copy_svo(&mut self, svo: &SVO<T>) -> Slot {
+        match *svo {
+            SVO::Voxel { data, .. } => self.alloc_leaf(data),
+            SVO::Octants(ref octants) => {
+                let node = self.alloc_node();
+                for ix in 0..8 {
+                    let child = self.copy_svo(&octants[ix as usize]);
+                    self.nodes[node as usize].set_child(ix, child);
+                }
+                Slot::Node(node)
+            }
+        }
+    }
+
+    pub fn to_svo(&self) -> SVO<T> {
+        self.slot_to_svo(self.root)
+    }
+
+    fn slot_to_svo(&self, slot: Slot) -> SVO<T> {
+        match slot {
+            Slot::Leaf(leaf) => SVO::new_voxel(self.leaf(leaf)),
+            Slot::Node(node) => SVO::new_octants(|ix| self.slot_to_svo(self.child(node, ix))),
+        }
+    }
+
+    pub fn root(&self) -> Slot {
+        self.root
+    }
+
+    pub fn child(&self, node: u32, ix: u8) -> Slot {
+        self.nodes[node as usize].child(ix)
+    }
+
+    pub fn leaf(&self, leaf: u32) -> T {
+        self.leaves[leaf as usize]
+    }
+
+    // The number of nodes and leaves actually in the tree, ignoring freed slots.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - self.free_nodes.len()
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len() - self.free_leaves.len()
+    }
+
+    fn alloc_leaf(&mut self, data: T) -> Slot {
+        match self.free_leaves.pop() {
+            Some(leaf) => {
+                self.leaves[leaf as usize] = data;
+                Slot::Leaf(leaf)
+            },
+            None => {
+                self.leaves.push(data);
+                Slot::Leaf((self.leaves.len() - 1) as u32)
+            }
+        }
+    }
+
+    // Allocates a node whose children all still need to be set.
+    fn alloc_node(&mut self) -> u32 {
+        let node = Node { leaf_mask: 0, children: [0; 8] };
+        match self.free_nodes.pop() {
+            Some(ix) => {
+                self.nodes[ix as usize] = node;
+                ix
+            },
+            None => {
+                self.nodes.push(node);
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+
+    fn free(&mut self, slot: Slot) {
+        match slot {
+            Slot::Leaf(leaf) => self.free_leaves.push(leaf),
+            Slot::Node(node) => {
+                for ix in 0..8 {
+                    let child = self.child(node, ix);
+                    self.free(child);
+                }
+                self.free_nodes.push(node);
+            }
+        }
+    }
+
+    // Follow an index, splitting voxels as necessary. The set the block at the target to a Voxel with the specified data.
+    // Then go back up the tree, recombining if we've transformed all the octants in a node to the same voxel.
+    pub fn set_block(&mut self, index: &[u8], new_data: T) {
+        let root = self.root;
+        self.root = self.set_voxel_from(root, index, new_data);
+    }
+
+    fn set_voxel_from(&mut self, slot: Slot, index: &[u8], new_data: T) -> Slot {
+        if let Slot::Leaf(leaf) = slot {
+            if self.leaf(leaf) == new_data { return slot; } // nothing to do
+        }
+
+        match index.split_first() {
+            // Overwrite whatever's here with the new voxel.
+            None => {
+                self.free(slot);
+                self.alloc_leaf(new_data)
+            },
+
+            // We need to go deeper.
+            Some((&ix, rest)) => {
+                let node = match slot {
+                    Slot::Leaf(leaf) => self.subdivide_voxel(leaf),
+                    Slot::Node(node) => node,
+                };
+                let child = self.child(node, ix);
+                let new_child = self.set_voxel_from(child, rest, new_data);
+                self.nodes[node as usize].set_child(ix, new_child);
+                self.recombine(node)
+            }
+        }
+    }
+
+    // Replace a leaf with a node of eight copies of it. The old leaf becomes the first child.
+    fn subdivide_voxel(&mut self, leaf: u32) -> u32 {
+        let data = self.leaf(leaf);
+        let node = self.alloc_node();
+        self.nodes[node as usize].set_child(0, Slot::Leaf(leaf));
+        for ix in 1..8 {
+            let child = self.alloc_leaf(data);
+            self.nodes[node as usize].set_child(ix, child);
+        }
+        node
+    }
+
+    // If all of a node's children are the same voxel, replace it with its first child.
+    fn recombine(&mut self, node: u32) -> Slot {
+        let Node { leaf_mask, children } = self.nodes[node as usize];
+        if leaf_mask != 0xFF { return Slot::Node(node); }
+        let first_data = self.leaf(children[0]);
+        if children[1..].iter().any(|&leaf| self.leaf(leaf) != first_data) {
+            return Slot::Node(node);
+        }
+
+        self.free_leaves.extend_from_slice(&children[1..]);
+        self.free_nodes.push(node);
+        Slot::Leaf(children[0])
+    }
+
+    // Cast a ray into the octree and return the position of collision with a non-empty voxel (if any).
+    // See SVO::cast_ray.
+    pub fn cast_ray(&self, ray_origin: Vector3<f32>, ray_dir: Vector3<f32>) -> Option<Vector3<f32>> {
+        let root = ArenaNode { arena: self, slot: self.root };
+        cast_ray_octree(root, ray_origin, ray_dir, &|data: &T| !data.is_empty())
+    }
+
+    // Uses the same format as WriteSVO::write_svo, so files can be loaded into either representation.
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()> {
+        self.write_slot(writer, self.root)
+    }
+
+    fn write_slot<W: Write + ?Sized>(&self, writer: &mut W, slot: Slot) -> Result<()> {
+        match slot {
+            Slot::Leaf(leaf) => {
+                try!{ writer.write_all(&[VOXEL_TAG]) };
+                self.leaf(leaf).write_to(writer)
+            },
+            Slot::Node(node) => {
+                try!{ writer.write_all(&[OCTANT_TAG]) };
+                for ix in 0..8 { try!{ self.write_slot(writer, self.child(node, ix)) }; }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn read_from<R: Read + ?Sized>(reader: &mut R) -> Result<ArenaSVO<T>> {
+        let mut arena = ArenaSVO::with_root(Slot::Leaf(0));
+        arena.root = try!{ arena.read_slot(reader) };
+        Ok(arena)
+    }
+
+    fn read_slot<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<Slot> {
+        let mut b = [0];
+        let bytes_read = try!{ reader.read(&mut b) };
+        if bytes_read == 0 {
+            let msg = "Unexpected end of input stream.";
+            return Err(Error::new(ErrorKind::InvalidData, msg));
+        }
+
+        match b[0] {
+            VOXEL_TAG => {
+                let data = try!{ T::read_from(reader) };
+                Ok(self.alloc_leaf(data))
+            },
+            OCTANT_TAG => {
+                let node = self.alloc_node();
+                for ix in 0..8 {
+                    let child = try!{ self.read_slot(reader) };
+                    self.nodes[node as usize].set_child(ix, child);
+                }
+                Ok(Slot::Node(node))
+            },
+            other => {
+                let msg = format!("Invalid SVO type specifier '{}' found", other);
+                Err(Error::new(ErrorKind::InvalidData, msg))
+            }
+        }
+    }
+}
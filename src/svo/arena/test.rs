@@ -0,0 +1,84 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use std::io::Cursor;
+use svo::*;
+use svo::save_load::{ReadSVO, WriteSVO};
+
+fn sanitise_path(path: Vec<u8>) -> Vec<u8> {
+    path.into_iter().take(4).map(|ix| ix % 8).collect()
+}
+
+#[test]
+fn svo_round_trip() {
+    fn check_svo_round_trip(svo: SVO) -> bool {
+        ArenaSVO::from_svo(&svo).to_svo() == svo
+    }
+    quickcheck(check_svo_round_trip as fn(SVO) -> bool)
+}
+
+#[test]
+fn set_block_matches_svo() {
+    fn check_set_block_matches_svo(mut svo: SVO, edits: Vec<(Vec<u8>, bool)>) -> bool {
+        let mut arena = ArenaSVO::from_svo(&svo);
+        for (path, solid) in edits {
+            let path = sanitise_path(path);
+            let data = VoxelData::new(solid as i32);
+            svo.set_block(&path, data);
+            arena.set_block(&path, data);
+        }
+        arena.to_svo() == svo
+    }
+    quickcheck(check_set_block_matches_svo as fn(SVO, Vec<(Vec<u8>, bool)>) -> bool)
+}
+
+#[test]
+fn recombining_frees_slots() {
+    let mut arena = ArenaSVO::new_voxel(VoxelData::new(1));
+    arena.set_block(&[1, 3, 5], VoxelData::new(0));
+    assert_eq!(arena.node_count(), 3);
+    assert_eq!(arena.leaf_count(), 22);
+
+    arena.set_block(&[1, 3, 5], VoxelData::new(1));
+    assert_eq!(arena.node_count(), 0);
+    assert_eq!(arena.leaf_count(), 1);
+    assert_eq!(arena.to_svo(), SVO::new_voxel(VoxelData::new(1)));
+
+    // The freed slots get reused rather than growing the arena.
+    arena.set_block(&[6, 2, 0], VoxelData::new(0));
+    assert_eq!(arena.node_count(), 3);
+    assert_eq!(arena.leaf_count(), 22);
+    assert_eq!(arena.nodes.len(), 3);
+    assert_eq!(arena.leaves.len(), 22);
+}
+
+#[test]
+fn cast_ray_matches_svo() {
+    fn check_cast_ray_matches_svo(svo: SVO, origin_tuple: (f32, f32, f32), dir_tuple: (f32, f32, f32)) -> bool {
+        let origin = Vector3::new(origin_tuple.0, origin_tuple.1, origin_tuple.2);
+        let dir = Vector3::new(dir_tuple.0, dir_tuple.1, dir_tuple.2);
+        ArenaSVO::from_svo(&svo).cast_ray(origin, dir) == svo.cast_ray(origin, dir)
+    }
+    quickcheck(check_cast_ray_matches_svo as fn(SVO, (f32, f32, f32), (f32, f32, f32)) -> bool)
+}
+
+#[test]
+fn save_load_interchangeable() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 3], VoxelData::new(2));
+    let arena = ArenaSVO::from_svo(&svo);
+
+    let mut svo_bytes: Vec<u8> = vec![];
+    svo_bytes.write_svo(&svo).unwrap();
+    let mut arena_bytes: Vec<u8> = vec![];
+    arena_bytes.write_arena_svo(&arena).unwrap();
+    assert_eq!(svo_bytes, arena_bytes);
+
+    let loaded: ArenaSVO = Cursor::new(svo_bytes).read_arena_svo().unwrap();
+    assert_eq!(loaded.to_svo(), svo);
+}
+
+#[test]
+fn load_invalid_tag() {
+    let result: ::std::io::Result<ArenaSVO> = Cursor::new(vec![7u8]).read_arena_svo();
+    assert!(result.is_err());
+}
@@ -0,0 +1,153 @@
+use nalgebra::{Vector3, Norm};
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// A shape given by its signed distance function, in the SVO's unit cube: negative inside the
+// shape and positive outside. The distance may be an underestimate, but never an overestimate,
+// or whole octants can be wrongly treated as inside or outside.
+pub trait Brush {
+    fn distance(&self, point: Vector3<f32>) -> f32;
+}
+
+// Any closure from a point to a signed distance is a brush.
+impl<F: Fn(Vector3<f32>) -> f32> Brush for F {
+    fn distance(&self, point: Vector3<f32>) -> f32 {
+        self(point)
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Sphere {
+    pub centre: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl Brush for Sphere {
+    fn distance(&self, point: Vector3<f32>) -> f32 {
+        (point - self.centre).norm() - self.radius
+    }
+}
+
+// A cylinder standing upright along the y axis.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Cylinder {
+    pub centre: Vector3<f32>,
+    pub radius: f32,
+    pub half_height: f32,
+}
+
+impl Brush for Cylinder {
+    fn distance(&self, point: Vector3<f32>) -> f32 {
+        let p = point - self.centre;
+        let radial = (p.x * p.x + p.z * p.z).sqrt() - self.radius;
+        let vertical = p.y.abs() - self.half_height;
+        let outside = (radial.max(0.).powi(2) + vertical.max(0.).powi(2)).sqrt();
+        radial.max(vertical).min(0.) + outside
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Cuboid {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Brush for Cuboid {
+    fn distance(&self, point: Vector3<f32>) -> f32 {
+        let centre = (self.min + self.max) * 0.5;
+        let half_extents = (self.max - self.min) * 0.5;
+        let p = point - centre;
+        let q = Vector3::new(p.x.abs(), p.y.abs(), p.z.abs()) - half_extents;
+        let outside = Vector3::new(q.x.max(0.), q.y.max(0.), q.z.max(0.)).norm();
+        q.x.max(q.y).max(q.z).min(0.) + outside
+    }
+}
+
+// What to do to the voxels inside a brush.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum BrushOp<T> {
+    // Fill them with the data.
+    Union(T),
+    // Replace them with LeafData::empty.
+    Subtract,
+    // Change the data of the non-empty ones, leaving the empty ones alone.
+    Paint(T),
+}
+
+impl<T: LeafData> SVO<T> {
+    // Octants that are entirely inside or outside of the brush aren't split any further. The ones
+    // on its surface are split down to max_depth, where they count as inside if their centre is.
+    pub fn apply_brush<B: Brush>(&mut self, brush: &B, op: BrushOp<T>, max_depth: u32) {
+        self.apply_brush_with(&RegistrationFunctions::blank(), brush, op, max_depth);
+    }
+
+    // Like apply_brush, but every leaf that appears or disappears is registered or deregistered.
+    pub fn apply_brush_with<B: Brush>(&mut self, registration: &RegistrationFunctions<T>, brush: &B, op: BrushOp<T>, max_depth: u32) {
+        self.apply_brush_helper(registration, brush, &op, max_depth, Vector3::new(0., 0., 0.), 0);
+    }
+
+    fn apply_brush_helper<B: Brush>(&mut self,
+                                    registration: &RegistrationFunctions<T>,
+                                    brush: &B,
+                                    op: &BrushOp<T>,
+                                    depth_left: u32,
+                                    origin: Vector3<f32>,
+                                    depth: i32) {
+        let side_len = 1. / (1u64 << depth) as f32;
+        let half_diagonal = side_len * 0.5 * 3f32.sqrt();
+        let distance = brush.distance(origin + Vector3::new(0.5, 0.5, 0.5) * side_len);
+
+        // Entirely outside.
+        if distance >= half_diagonal { return; }
+
+        // Entirely inside, or as small as we're allowed to go.
+        if distance <= -half_diagonal || depth_left == 0 {
+            if distance <= 0. { self.apply_op(registration, op, origin, depth); }
+            return;
+        }
+
+        // Painting empty space, or filling with what's already there, changes nothing.
+        if let Some(voxel_data) = self.get_voxel_data() {
+            let unchanged = match *op {
+                BrushOp::Union(data) => voxel_data == data,
+                BrushOp::Subtract => voxel_data == T::empty(),
+                BrushOp::Paint(data) => voxel_data.is_empty() || voxel_data == data,
+            };
+            if unchanged { return; }
+            self.subdivide_voxel_with(registration, origin, depth);
+        }
+
+        if let SVO::Octants(ref mut octants) = *self {
+            for ix in 0..8 {
+                octants[ix as usize].apply_brush_helper(registration, brush, op, depth_left - 1,
+                                                        origin + offset(ix, depth), depth + 1);
+            }
+        }
+        self.recombine_svo_with(registration, origin, depth);
+    }
+
+    // Apply the op to this whole subtree.
+    fn apply_op(&mut self, registration: &RegistrationFunctions<T>, op: &BrushOp<T>, origin: Vector3<f32>, depth: i32) {
+        let new_data = match *op {
+            BrushOp::Union(data) => data,
+            BrushOp::Subtract => T::empty(),
+            BrushOp::Paint(data) => match self.get_voxel_data() {
+                Some(old_data) => if old_data.is_empty() { return } else { data },
+                None => {
+                    if let SVO::Octants(ref mut octants) = *self {
+                        for ix in 0..8 {
+                            octants[ix as usize].apply_op(registration, op, origin + offset(ix, depth), depth + 1);
+                        }
+                    }
+                    self.recombine_svo_with(registration, origin, depth);
+                    return;
+                }
+            },
+        };
+        if self.get_voxel_data() == Some(new_data) { return; }
+        self.deregister_all(registration);
+        *self = SVO::new_registered_voxel(registration, new_data, origin, depth);
+    }
+}
@@ -0,0 +1,108 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use svo::*;
+
+// What apply_brush should do at the given depth, one voxel at a time.
+fn brute_force<B: Brush>(svo: &SVO, brush: &B, op: BrushOp<VoxelData>, depth: u32) -> SVO {
+    let mut expected = svo.clone();
+    let size = 1 << depth;
+    for x in 0..size {
+        for y in 0..size {
+            for z in 0..size {
+                let coord = VoxelCoord::new(x, y, z, depth);
+                let centre = coord.origin() + Vector3::new(0.5, 0.5, 0.5) * coord.side_len();
+                if brush.distance(centre) > 0. { continue; }
+                // Painting only touches solid voxels, which may be bigger or smaller than the coord.
+                match op {
+                    BrushOp::Union(data) => expected.set_block_at(coord, data),
+                    BrushOp::Subtract => expected.set_block_at(coord, VoxelData::empty()),
+                    BrushOp::Paint(data) => {
+                        let (old_data, _) = svo.voxel_at(centre).unwrap();
+                        if !old_data.is_empty() { expected.set_block_at(coord, data); }
+                    }
+                }
+            }
+        }
+    }
+    expected
+}
+
+#[test]
+fn sphere_union() {
+    let sphere = Sphere { centre: Vector3::new(0.5, 0.5, 0.5), radius: 0.3 };
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.apply_brush(&sphere, BrushOp::Union(VoxelData::new(1)), 4);
+
+    let expected = brute_force(&SVO::new_voxel(VoxelData::new(0)), &sphere, BrushOp::Union(VoxelData::new(1)), 4);
+    assert_eq!(svo, expected);
+    assert_eq!(svo.voxel_at(Vector3::new(0.5, 0.5, 0.5)).unwrap().0, VoxelData::new(1));
+    assert_eq!(svo.voxel_at(Vector3::new(0.1, 0.1, 0.1)).unwrap().0, VoxelData::new(0));
+}
+
+#[test]
+fn covered_octants_are_not_split() {
+    // The sphere covers the whole cube, so the result is a single voxel no matter the depth.
+    let sphere = Sphere { centre: Vector3::new(0.5, 0.5, 0.5), radius: 2. };
+    let mut svo = SVO::floor();
+    svo.apply_brush(&sphere, BrushOp::Union(VoxelData::new(3)), 10);
+    assert_eq!(svo, SVO::new_voxel(VoxelData::new(3)));
+
+    // And one that misses leaves the tree alone.
+    let sphere = Sphere { centre: Vector3::new(3., 3., 3.), radius: 1. };
+    let mut svo = SVO::floor();
+    svo.apply_brush(&sphere, BrushOp::Union(VoxelData::new(3)), 10);
+    assert_eq!(svo, SVO::floor());
+}
+
+#[test]
+fn cylinder_subtract() {
+    let cylinder = Cylinder { centre: Vector3::new(0.5, 0.25, 0.5), radius: 0.2, half_height: 0.1 };
+    let mut svo = SVO::floor();
+    svo.apply_brush(&cylinder, BrushOp::Subtract, 4);
+    assert_eq!(svo, brute_force(&SVO::floor(), &cylinder, BrushOp::Subtract, 4));
+    assert_eq!(svo.voxel_at(Vector3::new(0.5, 0.25, 0.5)).unwrap().0, VoxelData::new(0));
+    assert_eq!(svo.voxel_at(Vector3::new(0.5, 0.05, 0.5)).unwrap().0, VoxelData::new(1));
+}
+
+#[test]
+fn cuboid_paint() {
+    let cuboid = Cuboid { min: Vector3::new(0.2, 0.2, 0.2), max: Vector3::new(0.8, 0.8, 0.8) };
+    let mut svo = SVO::floor();
+    svo.apply_brush(&cuboid, BrushOp::Paint(VoxelData::new(2)), 3);
+    assert_eq!(svo, brute_force(&SVO::floor(), &cuboid, BrushOp::Paint(VoxelData::new(2)), 3));
+
+    // The air above the floor isn't painted.
+    assert_eq!(svo.voxel_at(Vector3::new(0.5, 0.7, 0.5)).unwrap().0, VoxelData::new(0));
+    assert_eq!(svo.voxel_at(Vector3::new(0.5, 0.4, 0.5)).unwrap().0, VoxelData::new(2));
+}
+
+#[test]
+fn closure_brush() {
+    // A slab through the middle of the y axis.
+    let slab = |p: Vector3<f32>| (p.y - 0.5).abs() - 0.125;
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.apply_brush(&slab, BrushOp::Union(VoxelData::new(1)), 3);
+
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    expected.fill_coords(VoxelCoord::new(0, 3, 0, 3), VoxelCoord::new(7, 4, 7, 3), VoxelData::new(1));
+    assert_eq!(svo, expected);
+}
+
+#[test]
+fn sphere_matches_brute_force() {
+    fn check_sphere_matches_brute_force(svo: SVO, centre: (u8, u8, u8), radius: u8, op: u8) -> bool {
+        let sphere = Sphere {
+            centre: Vector3::new(centre.0 as f32, centre.1 as f32, centre.2 as f32) / 200.,
+            radius: (radius % 100) as f32 / 200.,
+        };
+        let op = match op % 3 {
+            0 => BrushOp::Union(VoxelData::new(2)),
+            1 => BrushOp::Subtract,
+            _ => BrushOp::Paint(VoxelData::new(3)),
+        };
+        let mut brushed = svo.clone();
+        brushed.apply_brush(&sphere, op, 3);
+        brushed.same_voxels(&brute_force(&svo, &sphere, op, 3), 3)
+    }
+    quickcheck(check_sphere_matches_brute_force as fn(SVO, (u8, u8, u8), u8, u8) -> bool)
+}
@@ -0,0 +1,104 @@
+use nalgebra::Vector3;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// The subtree at an index path that an edit may have changed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    pub index: Vec<u8>,
+}
+
+impl Change {
+    // The corners of the changed cube, in the SVO's unit cube.
+    pub fn bounds(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let (origin, side_len) = index_position(&self.index);
+        (origin, origin + Vector3::new(1.0, 1.0, 1.0) * side_len)
+    }
+
+    // Whether the other index path is inside the changed subtree.
+    pub fn covers(&self, index: &[u8]) -> bool {
+        index.starts_with(&self.index)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct SubscriptionId(usize);
+
+// An SVO that remembers which subtrees have been edited since the dirty regions were last taken,
+// and tells its subscribers about every edit as it happens.
+pub struct TrackedSVO<T = VoxelData> {
+    svo: SVO<T>,
+    dirty: Vec<Change>,
+    subscribers: Vec<(SubscriptionId, Box<FnMut(&Change)>)>,
+    next_subscription: usize,
+}
+
+impl<T: LeafData> TrackedSVO<T> {
+    pub fn new(svo: SVO<T>) -> TrackedSVO<T> {
+        TrackedSVO { svo: svo, dirty: Vec::new(), subscribers: Vec::new(), next_subscription: 0 }
+    }
+
+    pub fn svo(&self) -> &SVO<T> {
+        &self.svo
+    }
+
+    pub fn into_svo(self) -> SVO<T> {
+        self.svo
+    }
+
+    pub fn set_block(&mut self, index: &[u8], new_data: T) {
+        self.edit(index, |svo| svo.set_block(index, new_data));
+    }
+
+    // Apply an edit that only changes the subtree at the index path, and mark that subtree dirty.
+    // Edits that can change anything, like fill_box or apply_brush, should use the empty path.
+    pub fn edit<F, R>(&mut self, index: &[u8], edit: F) -> R
+            where F: FnOnce(&mut SVO<T>) -> R {
+        let result = edit(&mut self.svo);
+        self.mark_dirty(index);
+        result
+    }
+
+    pub fn mark_dirty(&mut self, index: &[u8]) {
+        let change = Change { index: index.to_vec() };
+        for &mut (_, ref mut subscriber) in &mut self.subscribers {
+            subscriber(&change);
+        }
+
+        // Only keep the largest dirty subtrees, since they cover everything inside them.
+        if self.dirty.iter().any(|dirty| dirty.covers(index)) { return; }
+        self.dirty.retain(|dirty| !change.covers(&dirty.index));
+        self.dirty.push(change);
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    pub fn dirty(&self) -> &[Change] {
+        &self.dirty
+    }
+
+    // The subtrees changed since the last call, none of which are inside another.
+    pub fn take_dirty(&mut self) -> Vec<Change> {
+        ::std::mem::replace(&mut self.dirty, Vec::new())
+    }
+
+    // Call the function with every change made from now on, until unsubscribed.
+    pub fn subscribe<F>(&mut self, subscriber: F) -> SubscriptionId
+            where F: FnMut(&Change) + 'static {
+        let id = SubscriptionId(self.next_subscription);
+        self.next_subscription += 1;
+        self.subscribers.push((id, Box::new(subscriber)));
+        id
+    }
+
+    // Returns false if the subscription had already been removed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|&(subscription, _)| subscription != id);
+        self.subscribers.len() != len
+    }
+}
@@ -0,0 +1,75 @@
+use nalgebra::{Vector3, ApproxEq};
+use std::cell::RefCell;
+use std::rc::Rc;
+use svo::*;
+
+#[test]
+fn edits_mark_dirty() {
+    let mut svo = TrackedSVO::new(SVO::floor());
+    assert!(!svo.is_dirty());
+    svo.set_block(&[2, 1], VoxelData::new(1));
+    svo.set_block(&[5], VoxelData::new(1));
+    assert_eq!(svo.dirty(), &[Change { index: vec![2, 1] }, Change { index: vec![5] }]);
+
+    let mut expected = SVO::floor();
+    expected.set_block(&[2, 1], VoxelData::new(1));
+    expected.set_block(&[5], VoxelData::new(1));
+    assert_eq!(svo.svo(), &expected);
+
+    assert_eq!(svo.take_dirty().len(), 2);
+    assert!(!svo.is_dirty());
+}
+
+#[test]
+fn dirty_subtrees_are_merged() {
+    let mut svo = TrackedSVO::new(SVO::floor());
+    svo.set_block(&[2, 1, 3], VoxelData::new(1));
+    svo.set_block(&[2, 6], VoxelData::new(1));
+    svo.set_block(&[2, 1], VoxelData::new(2));
+    // [2, 1] covers the earlier [2, 1, 3].
+    assert_eq!(svo.dirty(), &[Change { index: vec![2, 6] }, Change { index: vec![2, 1] }]);
+
+    svo.edit(&[2], |svo| svo.set_block(&[2, 0], VoxelData::new(1)));
+    assert_eq!(svo.dirty(), &[Change { index: vec![2] }]);
+    svo.set_block(&[2, 7], VoxelData::new(1));
+    assert_eq!(svo.dirty(), &[Change { index: vec![2] }]);
+}
+
+#[test]
+fn change_bounds() {
+    let (min, max) = Change { index: vec![3, 4] }.bounds();
+    assert_approx_eq_eps!(min, Vector3::new(0.5, 0.5, 0.25), 1e-6);
+    assert_approx_eq_eps!(max, Vector3::new(0.75, 0.75, 0.5), 1e-6);
+
+    let (min, max) = Change { index: vec![] }.bounds();
+    assert_approx_eq_eps!(min, Vector3::new(0.0, 0.0, 0.0), 1e-6);
+    assert_approx_eq_eps!(max, Vector3::new(1.0, 1.0, 1.0), 1e-6);
+}
+
+#[test]
+fn subscribers_see_every_change() {
+    let mut svo = TrackedSVO::new(SVO::floor());
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let subscription = {
+        let seen = seen.clone();
+        svo.subscribe(move |change| seen.borrow_mut().push(change.index.clone()))
+    };
+
+    svo.set_block(&[2, 1], VoxelData::new(1));
+    svo.set_block(&[2], VoxelData::new(1));
+    svo.take_dirty();
+    svo.set_block(&[2, 1], VoxelData::new(0));
+    assert_eq!(*seen.borrow(), vec![vec![2, 1], vec![2], vec![2, 1]]);
+
+    assert!(svo.unsubscribe(subscription));
+    assert!(!svo.unsubscribe(subscription));
+    svo.set_block(&[4], VoxelData::new(1));
+    assert_eq!(seen.borrow().len(), 3);
+}
+
+#[test]
+fn deep_change_bounds() {
+    let (min, max) = Change { index: vec![1; 40] }.bounds();
+    assert_approx_eq_eps!(min, Vector3::new(1.0, 0.0, 0.0), 1e-6);
+    assert_approx_eq_eps!(max, Vector3::new(1.0, 0.0, 0.0), 1e-6);
+}
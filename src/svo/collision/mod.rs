@@ -0,0 +1,105 @@
+use nalgebra::{Vector3, zero};
+use std::f32;
+use svo::*;
+use svo::traverse::Leaves;
+
+#[cfg(test)]
+mod test;
+
+// Where a moving box first hits something.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Contact {
+    // The fraction of the displacement travelled before the hit, from 0 to 1.
+    pub time: f32,
+    // The normal of the face that was hit, pointing back towards the box.
+    // This is zero when the box already overlaps something at the start.
+    pub normal: Vector3<f32>,
+}
+
+impl<T: LeafData> SVO<T> {
+    // Whether any solid voxel overlaps the box. Boxes that only touch a voxel's face don't count,
+    // so a box resting on the ground doesn't intersect it.
+    pub fn intersects_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> bool {
+        self.solid_leaves_in_aabb(min, max).next().is_some()
+    }
+
+    // Every solid voxel overlapping the box, using the same rule as intersects_aabb.
+    pub fn solid_leaves_in_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> SolidLeavesInAabb<T> {
+        SolidLeavesInAabb { leaves: Leaves::new(self, Some((min, max))), min: min, max: max }
+    }
+
+    // Move the box from min to max by the displacement, and return where it first hits a solid voxel.
+    pub fn sweep_aabb(&self, min: Vector3<f32>, max: Vector3<f32>, displacement: Vector3<f32>) -> Option<Contact> {
+        let end_min = min + displacement;
+        let end_max = max + displacement;
+        let swept_min = Vector3::new(min.x.min(end_min.x), min.y.min(end_min.y), min.z.min(end_min.z));
+        let swept_max = Vector3::new(max.x.max(end_max.x), max.y.max(end_max.y), max.z.max(end_max.z));
+
+        Leaves::new(self, Some((swept_min, swept_max)))
+            .filter(|leaf| !leaf.data.is_empty())
+            .filter_map(|leaf| {
+                let leaf_max = leaf.origin + Vector3::new(leaf.side_len, leaf.side_len, leaf.side_len);
+                sweep_against(min, max, displacement, leaf.origin, leaf_max)
+            })
+            .fold(None, |first: Option<Contact>, contact| match first {
+                Some(first) if first.time <= contact.time => Some(first),
+                _ => Some(contact),
+            })
+    }
+}
+
+pub struct SolidLeavesInAabb<'a, T: 'a> {
+    leaves: Leaves<'a, T>,
+    min: Vector3<f32>,
+    max: Vector3<f32>,
+}
+
+impl<'a, T: LeafData> Iterator for SolidLeavesInAabb<'a, T> {
+    type Item = Leaf<'a, T>;
+
+    fn next(&mut self) -> Option<Leaf<'a, T>> {
+        let (min, max) = (self.min, self.max);
+        self.leaves.by_ref().find(|leaf| !leaf.data.is_empty() && overlaps_strictly(leaf.origin, leaf.side_len, min, max))
+    }
+}
+
+// Like svo::query::overlaps_box, but touching faces don't count on either side.
+fn overlaps_strictly(origin: Vector3<f32>, side_len: f32, min: Vector3<f32>, max: Vector3<f32>) -> bool {
+    let overlaps = |o: f32, lo: f32, hi: f32| o < hi && lo < o + side_len;
+    overlaps(origin.x, min.x, max.x) && overlaps(origin.y, min.y, max.y) && overlaps(origin.z, min.z, max.z)
+}
+
+// The slab test for a moving box against a still one: on each axis, find when the boxes start
+// and stop overlapping. They collide if there's a time where they overlap on every axis.
+fn sweep_against(min: Vector3<f32>, max: Vector3<f32>, displacement: Vector3<f32>,
+                 other_min: Vector3<f32>, other_max: Vector3<f32>) -> Option<Contact> {
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut entry_axis = 0;
+    for axis in 0..3 {
+        let d = displacement[axis];
+        let (axis_entry, axis_exit) = if d > 0. {
+            ((other_min[axis] - max[axis]) / d, (other_max[axis] - min[axis]) / d)
+        } else if d < 0. {
+            ((other_max[axis] - min[axis]) / d, (other_min[axis] - max[axis]) / d)
+        } else if min[axis] < other_max[axis] && other_min[axis] < max[axis] {
+            (f32::NEG_INFINITY, f32::INFINITY)
+        } else {
+            return None;
+        };
+        if axis_entry > entry {
+            entry = axis_entry;
+            entry_axis = axis;
+        }
+        exit = exit.min(axis_exit);
+    }
+
+    guard!(entry < exit && exit > 0. && entry <= 1.);
+    if entry < 0. {
+        // Already overlapping.
+        return Some(Contact { time: 0., normal: zero() });
+    }
+    let mut normal: Vector3<f32> = zero();
+    normal[entry_axis] = -displacement[entry_axis].signum();
+    Some(Contact { time: entry, normal: normal })
+}
@@ -0,0 +1,71 @@
+use nalgebra::{Vector3, ApproxEq, zero};
+use quickcheck::*;
+use svo::*;
+
+fn v(x: f32, y: f32, z: f32) -> Vector3<f32> {
+    Vector3::new(x, y, z)
+}
+
+#[test]
+fn intersects_floor() {
+    let svo = SVO::floor();
+    assert!(svo.intersects_aabb(v(0.1, 0.4, 0.1), v(0.2, 0.6, 0.2)));
+    // Resting on the floor, or against the side of it.
+    assert!(!svo.intersects_aabb(v(0.1, 0.5, 0.1), v(0.2, 0.7, 0.2)));
+    assert!(!svo.intersects_aabb(v(-0.5, 0.1, 0.1), v(0., 0.2, 0.2)));
+    assert!(!svo.intersects_aabb(v(2., 2., 2.), v(3., 3., 3.)));
+}
+
+#[test]
+fn solid_leaves_in_aabb() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 2], VoxelData::new(0));
+    let indices: Vec<Vec<u8>> = svo.solid_leaves_in_aabb(v(0.4, 0.3, 0.1), v(0.8, 0.5, 0.2))
+        .map(|leaf| leaf.index).collect();
+    assert_eq!(indices, vec![vec![0], vec![1, 3]]);
+}
+
+#[test]
+fn fall_onto_floor() {
+    let svo = SVO::floor();
+    let contact = svo.sweep_aabb(v(0.1, 0.8, 0.1), v(0.2, 0.9, 0.2), v(0., -0.6, 0.)).unwrap();
+    assert_approx_eq_eps!(contact.time, 0.5, 0.0001);
+    assert_eq!(contact.normal, v(0., 1., 0.));
+
+    // Not far enough to reach it.
+    assert_eq!(svo.sweep_aabb(v(0.1, 0.8, 0.1), v(0.2, 0.9, 0.2), v(0., -0.2, 0.)), None);
+}
+
+#[test]
+fn slide_along_floor() {
+    let svo = SVO::floor();
+    assert_eq!(svo.sweep_aabb(v(0.1, 0.5, 0.1), v(0.2, 0.6, 0.2), v(0.7, 0., 0.7)), None);
+    // Walking off the top and into a wall.
+    let mut svo = SVO::floor();
+    svo.set_block(&[3, 1], VoxelData::new(1));
+    let contact = svo.sweep_aabb(v(0.1, 0.5, 0.1), v(0.2, 0.6, 0.2), v(0.8, 0., 0.)).unwrap();
+    assert_approx_eq_eps!(contact.time, 0.6875, 0.0001);
+    assert_eq!(contact.normal, v(-1., 0., 0.));
+}
+
+#[test]
+fn already_overlapping() {
+    let svo = SVO::floor();
+    let contact = svo.sweep_aabb(v(0.1, 0.4, 0.1), v(0.2, 0.6, 0.2), v(0., 1., 0.)).unwrap();
+    assert_eq!(contact, Contact { time: 0., normal: zero() });
+}
+
+#[test]
+fn sweep_end_matches_intersects() {
+    // A sweep that doesn't hit anything leaves the box somewhere clear.
+    fn check_sweep(svo: SVO, start: (u8, u8, u8), displacement: (i8, i8, i8)) -> bool {
+        let min = v(start.0 as f32, start.1 as f32, start.2 as f32) / 255.;
+        let max = min + v(0.1, 0.1, 0.1);
+        let displacement = v(displacement.0 as f32, displacement.1 as f32, displacement.2 as f32) / 127.;
+        match svo.sweep_aabb(min, max, displacement) {
+            None => !svo.intersects_aabb(min + displacement, max + displacement) && !svo.intersects_aabb(min, max),
+            Some(contact) => contact.time >= 0. && contact.time <= 1.,
+        }
+    }
+    quickcheck(check_sweep as fn(SVO, (u8, u8, u8), (i8, i8, i8)) -> bool)
+}
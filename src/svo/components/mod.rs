@@ -0,0 +1,138 @@
+use nalgebra::Vector3;
+use std::collections::HashMap;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// Which solid leaves count as joined together.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Connectivity {
+    // Leaves sharing part of a face.
+    Face,
+    // Leaves sharing part of a face, an edge or a corner.
+    Full,
+}
+
+// A group of connected solid leaves.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Component<'a, T: 'a> {
+    // In octant index order.
+    pub leaves: Vec<Leaf<'a, T>>,
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl<'a, T> Component<'a, T> {
+    // Whether the component reaches the side of the SVO's unit cube.
+    pub fn touches(&self, face: Face) -> bool {
+        let axis = face.axis() as usize;
+        if face.is_positive() { self.max[axis] >= 1. } else { self.min[axis] <= 0. }
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.leaves.iter().map(|leaf| leaf.side_len.powi(3)).sum()
+    }
+}
+
+impl<T: LeafData> SVO<T> {
+    // Every group of connected solid leaves, ordered by their first leaf.
+    pub fn components(&self, connectivity: Connectivity) -> Vec<Component<T>> {
+        let leaves: Vec<Leaf<T>> = self.leaves().filter(|leaf| !leaf.data.is_empty()).collect();
+        let ids: HashMap<Vec<u8>, usize> = leaves.iter().enumerate().map(|(id, leaf)| (leaf.index.clone(), id)).collect();
+        let mut parents: Vec<usize> = (0..leaves.len()).collect();
+
+        for (id, leaf) in leaves.iter().enumerate() {
+            let touching: Vec<Vec<u8>> = match connectivity {
+                Connectivity::Face => FACES.iter().flat_map(|&face| match self.neighbour(&leaf.index, face) {
+                    Neighbour::Outside => vec![],
+                    Neighbour::Larger(other) | Neighbour::Same(other) => vec![other.index],
+                    Neighbour::Subdivided(others) => others.into_iter().map(|other| other.index).collect(),
+                }).collect(),
+                // Every leaf whose closed box touches the leaf's closed box, on any side.
+                Connectivity::Full => {
+                    let max = leaf.origin + Vector3::new(leaf.side_len, leaf.side_len, leaf.side_len);
+                    let mut touching = Vec::new();
+                    self.touching_leaves(leaf.origin, max, Vector3::new(0., 0., 0.), 1., &mut Vec::new(), &mut touching);
+                    touching.retain(|index| *index != leaf.index);
+                    touching
+                }
+            };
+            for other in touching.iter().filter_map(|index| ids.get(index)) {
+                union(&mut parents, id, *other);
+            }
+        }
+
+        let mut components: Vec<Component<T>> = Vec::new();
+        let mut component_ids = HashMap::new();
+        for (id, leaf) in leaves.into_iter().enumerate() {
+            let root = find(&mut parents, id);
+            let next_id = components.len();
+            let component_id = *component_ids.entry(root).or_insert(next_id);
+            if component_id == next_id {
+                components.push(Component { leaves: Vec::new(), min: leaf.origin, max: leaf.origin });
+            }
+            let component = &mut components[component_id];
+            let far_corner = leaf.origin + Vector3::new(leaf.side_len, leaf.side_len, leaf.side_len);
+            for axis in 0..3 {
+                component.min[axis] = component.min[axis].min(leaf.origin[axis]);
+                component.max[axis] = component.max[axis].max(far_corner[axis]);
+            }
+            component.leaves.push(leaf);
+        }
+        components
+    }
+
+    // The index paths of the leaves that touch the box from min to max, including their boundaries.
+    fn touching_leaves(&self,
+                       min: Vector3<f32>,
+                       max: Vector3<f32>,
+                       origin: Vector3<f32>,
+                       side_len: f32,
+                       index: &mut Vec<u8>,
+                       touching: &mut Vec<Vec<u8>>) {
+        let touches = |axis: usize| origin[axis] <= max[axis] && min[axis] <= origin[axis] + side_len;
+        if !(touches(0) && touches(1) && touches(2)) { return; }
+        match *self {
+            SVO::Voxel { .. } => touching.push(index.clone()),
+            SVO::Octants(ref octants) => {
+                let child_len = side_len / 2.;
+                for ix in 0..8 {
+                    index.push(ix);
+                    octants[ix as usize].touching_leaves(min, max, origin + offset_float(ix, child_len), child_len, index, touching);
+                    index.pop();
+                }
+            }
+        }
+    }
+
+    // The components that don't reach the anchor, such as Face::NegY for the ones floating above the ground.
+    pub fn floating_components(&self, connectivity: Connectivity, anchor: Face) -> Vec<Component<T>> {
+        self.components(connectivity).into_iter().filter(|component| !component.touches(anchor)).collect()
+    }
+
+    // Replace every floating component with LeafData::empty, returning how many leaves were removed.
+    pub fn remove_floating(&mut self, connectivity: Connectivity, anchor: Face) -> usize {
+        let indices: Vec<Vec<u8>> = self.floating_components(connectivity, anchor).into_iter()
+            .flat_map(|component| component.leaves.into_iter().map(|leaf| leaf.index))
+            .collect();
+        for index in &indices {
+            self.set_block(index, T::empty());
+        }
+        indices.len()
+    }
+}
+
+fn find(parents: &mut Vec<usize>, id: usize) -> usize {
+    let parent = parents[id];
+    if parent == id { return id; }
+    let root = find(parents, parent);
+    parents[id] = root;
+    root
+}
+
+fn union(parents: &mut Vec<usize>, a: usize, b: usize) {
+    let (root_a, root_b) = (find(parents, a), find(parents, b));
+    // Keep the smaller id as the root so the first leaf of a component stays its root.
+    if root_a < root_b { parents[root_b] = root_a } else { parents[root_a] = root_b }
+}
@@ -0,0 +1,108 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use svo::*;
+
+fn indices(component: &Component<VoxelData>) -> Vec<Vec<u8>> {
+    component.leaves.iter().map(|leaf| leaf.index.clone()).collect()
+}
+
+// A floor along the bottom, a block resting on it, and two blocks at the top that only meet at an edge.
+fn islands() -> SVO {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[0, 0], VoxelData::new(1));
+    svo.set_block(&[0, 1], VoxelData::new(1));
+    svo.set_block(&[0, 2], VoxelData::new(2));
+    svo.set_block(&[2, 3], VoxelData::new(1));
+    svo.set_block(&[3, 6], VoxelData::new(1));
+    svo
+}
+
+#[test]
+fn face_components() {
+    let svo = islands();
+    let components = svo.components(Connectivity::Face);
+    assert_eq!(components.len(), 3);
+    assert_eq!(indices(&components[0]), vec![vec![0, 0], vec![0, 1], vec![0, 2]]);
+    assert_eq!(components[0].min, Vector3::new(0., 0., 0.));
+    assert_eq!(components[0].max, Vector3::new(0.5, 0.5, 0.25));
+    assert_eq!(indices(&components[1]), vec![vec![2, 3]]);
+    assert_eq!(indices(&components[2]), vec![vec![3, 6]]);
+    assert_eq!(components[2].min, Vector3::new(0.5, 0.75, 0.25));
+    assert_eq!(components[2].volume(), 1. / 64.);
+}
+
+#[test]
+fn full_components() {
+    let svo = islands();
+    let components = svo.components(Connectivity::Full);
+    assert_eq!(components.len(), 2);
+    assert_eq!(indices(&components[1]), vec![vec![2, 3], vec![3, 6]]);
+    assert_eq!(components[1].max, Vector3::new(0.75, 1., 0.5));
+}
+
+#[test]
+fn floating() {
+    let svo = islands();
+    assert_eq!(svo.floating_components(Connectivity::Face, Face::NegY).len(), 2);
+    let hanging = svo.floating_components(Connectivity::Full, Face::PosY);
+    assert_eq!(hanging.len(), 1);
+    assert_eq!(indices(&hanging[0])[0], vec![0, 0]);
+}
+
+#[test]
+fn remove_floating() {
+    let mut svo = islands();
+    assert_eq!(svo.remove_floating(Connectivity::Face, Face::NegY), 2);
+    let mut expected = islands();
+    expected.set_block(&[2, 3], VoxelData::new(0));
+    expected.set_block(&[3, 6], VoxelData::new(0));
+    assert_eq!(svo, expected);
+    assert_eq!(svo.remove_floating(Connectivity::Face, Face::NegY), 0);
+}
+
+#[test]
+fn components_cover_solid_leaves() {
+    // Every solid leaf is in exactly one component, and face components never join full ones.
+    fn check_components_cover_solid_leaves(svo: SVO) -> bool {
+        let solid_count = svo.leaves().filter(|leaf| !leaf.data.is_empty()).count();
+        let face = svo.components(Connectivity::Face);
+        let full = svo.components(Connectivity::Full);
+        face.iter().map(|component| component.leaves.len()).sum::<usize>() == solid_count &&
+            full.iter().map(|component| component.leaves.len()).sum::<usize>() == solid_count &&
+            full.len() <= face.len()
+    }
+    quickcheck(check_components_cover_solid_leaves as fn(SVO) -> bool)
+}
+
+#[test]
+fn full_joins_mixed_direction_edges() {
+    // A is lower than B along x but higher along y, so they only share an edge.
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    let a = VoxelCoord::new(1, 2, 0, 2).to_index();
+    let b = VoxelCoord::new(2, 1, 0, 2).to_index();
+    svo.set_block(&a, VoxelData::new(1));
+    svo.set_block(&b, VoxelData::new(1));
+    assert_eq!(svo.components(Connectivity::Face).len(), 2);
+
+    let full = svo.components(Connectivity::Full);
+    assert_eq!(full.len(), 1);
+    assert_eq!(indices(&full[0]), vec![b, a]);
+}
+
+#[test]
+fn touching_leaves_share_full_components() {
+    // Any two solid leaves whose closed boxes touch are in the same full component.
+    fn check_touching_leaves(svo: SVO) -> bool {
+        let full = svo.components(Connectivity::Full);
+        let component_of = |index: &Vec<u8>| full.iter().position(|component| {
+            component.leaves.iter().any(|leaf| leaf.index == *index)
+        });
+        let solid: Vec<_> = svo.leaves().filter(|leaf| !leaf.data.is_empty()).collect();
+        solid.iter().all(|a| solid.iter().all(|b| {
+            let touches = (0..3).all(|axis| a.origin[axis] <= b.origin[axis] + b.side_len &&
+                                            b.origin[axis] <= a.origin[axis] + a.side_len);
+            !touches || component_of(&a.index) == component_of(&b.index)
+        }))
+    }
+    quickcheck(check_touching_leaves as fn(SVO) -> bool)
+}
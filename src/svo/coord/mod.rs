@@ -0,0 +1,105 @@
+use nalgebra::Vector3;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// A cube in the grid that splits an SVO into 2^depth voxels along each side.
+// The axes run the same way as world space, so (0, 0, 0) is the voxel at the origin and the
+// index path to a coordinate has bit i of each octant index set when the coordinate is in the
+// upper half of its parent along axis i (x = 0, y = 1, z = 2), matching svo::above_axis.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct VoxelCoord {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+    pub depth: u32,
+}
+
+// Depths beyond this can't be addressed with u32 coordinates.
+pub const MAX_DEPTH: u32 = 31;
+
+impl VoxelCoord {
+    pub fn new(x: u32, y: u32, z: u32, depth: u32) -> VoxelCoord {
+        assert!(depth <= MAX_DEPTH, "VoxelCoord depth {} is greater than {}", depth, MAX_DEPTH);
+        VoxelCoord { x: x, y: y, z: z, depth: depth }
+    }
+
+    // The number of voxels along each side of the grid at this coordinate's depth.
+    pub fn grid_size(&self) -> u32 {
+        1 << self.depth
+    }
+
+    pub fn in_bounds(&self) -> bool {
+        self.depth <= MAX_DEPTH && {
+            let size = self.grid_size();
+            self.x < size && self.y < size && self.z < size
+        }
+    }
+
+    // None if the path is too deep for u32 coordinates, see MAX_DEPTH.
+    pub fn from_index(index: &[u8]) -> Option<VoxelCoord> {
+        guard!(index.len() <= MAX_DEPTH as usize);
+        let (x, y, z) = index.iter().fold((0, 0, 0), |(x, y, z), &ix| {
+            ((x << 1) | (ix & 1) as u32,
+             (y << 1) | ((ix >> 1) & 1) as u32,
+             (z << 1) | ((ix >> 2) & 1) as u32)
+        });
+        Some(VoxelCoord::new(x, y, z, index.len() as u32))
+    }
+
+    pub fn to_index(&self) -> Vec<u8> {
+        assert!(self.in_bounds(), "{:?} is outside of the SVO", self);
+        (0..self.depth).rev().map(|bit| {
+            (((self.x >> bit) & 1) |
+             (((self.y >> bit) & 1) << 1) |
+             (((self.z >> bit) & 1) << 2)) as u8
+        }).collect()
+    }
+
+    // The corner of the voxel closest to the origin, in the SVO's unit cube.
+    pub fn origin(&self) -> Vector3<f32> {
+        Vector3::new(self.x as f32, self.y as f32, self.z as f32) * self.side_len()
+    }
+
+    pub fn side_len(&self) -> f32 {
+        1.0 / self.grid_size() as f32
+    }
+}
+
+impl<T: LeafData> SVO<T> {
+    // Whether the coordinate addresses a voxel inside this SVO.
+    pub fn contains(&self, coord: VoxelCoord) -> bool {
+        coord.in_bounds()
+    }
+
+    // The data of the voxel containing the coordinate, which may be bigger than the coordinate's voxel.
+    pub fn get_block(&self, coord: VoxelCoord) -> Option<T> {
+        guard!(self.contains(coord));
+        let mut svo = self;
+        for ix in coord.to_index() {
+            match *svo {
+                SVO::Voxel { data, .. } => return Some(data),
+                SVO::Octants(ref octants) => svo = &octants[ix as usize],
+            }
+        }
+        match *svo {
+            SVO::Voxel { data, .. } => Some(data),
+            // Anything smaller than the coordinate's voxel doesn't have a single value.
+            SVO::Octants(_) => None,
+        }
+    }
+
+    pub fn set_block_at(&mut self, coord: VoxelCoord, new_data: T) {
+        self.set_block(&coord.to_index(), new_data);
+    }
+}
+
+// The origin and side length of the node at an index path, in the SVO's unit cube. Unlike
+// VoxelCoord this works for paths of any depth.
+pub fn index_position(index: &[u8]) -> (Vector3<f32>, f32) {
+    index.iter().fold((Vector3::new(0., 0., 0.), 1.), |(origin, side_len), &ix| {
+        let child_len = side_len / 2.;
+        (origin + offset_float(ix, child_len), child_len)
+    })
+}
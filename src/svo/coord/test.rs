@@ -0,0 +1,95 @@
+use nalgebra::{ApproxEq, Vector3, zero};
+use quickcheck::*;
+use svo::*;
+
+fn sanitise_path(path: Vec<u8>) -> Vec<u8> {
+    path.into_iter().take(8).map(|ix| ix % 8).collect()
+}
+
+#[test]
+fn index_round_trip() {
+    fn check_index_round_trip(path: Vec<u8>) -> bool {
+        let path = sanitise_path(path);
+        VoxelCoord::from_index(&path).unwrap().to_index() == path
+    }
+    quickcheck(check_index_round_trip as fn(Vec<u8>) -> bool)
+}
+
+#[test]
+fn coord_round_trip() {
+    fn check_coord_round_trip(x: u32, y: u32, z: u32, depth: u8) -> TestResult {
+        let coord = VoxelCoord { x: x, y: y, z: z, depth: (depth % 10) as u32 };
+        if !coord.in_bounds() { return TestResult::discard(); }
+        TestResult::from_bool(VoxelCoord::from_index(&coord.to_index()) == Some(coord))
+    }
+    quickcheck(check_coord_round_trip as fn(u32, u32, u32, u8) -> TestResult)
+}
+
+#[test]
+fn origin_matches_offsets() {
+    fn check_origin_matches_offsets(path: Vec<u8>) -> bool {
+        let path = sanitise_path(path);
+        let expected = path.iter().enumerate()
+                           .fold(zero(), |origin: Vector3<f32>, (depth, &ix)| origin + offset(ix, depth as i32));
+        VoxelCoord::from_index(&path).unwrap().origin().approx_eq_eps(&expected, &0.0001)
+    }
+    quickcheck(check_origin_matches_offsets as fn(Vec<u8>) -> bool)
+}
+
+#[test]
+fn axis_conventions() {
+    // Each axis only sets its own bit, and the upper half of an axis has its bit set.
+    assert_eq!(VoxelCoord::new(1, 0, 0, 1).to_index(), vec![1]);
+    assert_eq!(VoxelCoord::new(0, 1, 0, 1).to_index(), vec![2]);
+    assert_eq!(VoxelCoord::new(0, 0, 1, 1).to_index(), vec![4]);
+    assert_eq!(VoxelCoord::new(3, 0, 2, 2).to_index(), vec![5, 1]);
+
+    // And svo::index agrees about which octant a point in the voxel is in.
+    for ix in 0..8 {
+        let coord = VoxelCoord::from_index(&[ix]).unwrap();
+        let centre = coord.origin() + Vector3::new(0.25, 0.25, 0.25);
+        assert_eq!(index(centre), ix);
+        assert_eq!(index(coord.origin()), ix);
+        assert_eq!(above_axis(ix), coord.origin() * 2.);
+    }
+}
+
+#[test]
+fn out_of_bounds() {
+    let svo = SVO::floor();
+    assert!(svo.contains(VoxelCoord::new(3, 3, 3, 2)));
+    assert!(!svo.contains(VoxelCoord::new(4, 0, 0, 2)));
+    assert!(!svo.contains(VoxelCoord::new(0, 1, 0, 0)));
+    assert_eq!(svo.get_block(VoxelCoord::new(0, 0, 4, 2)), None);
+}
+
+#[test]
+fn get_and_set_blocks() {
+    let mut svo = SVO::floor();
+
+    // The floor is the bottom half of the y axis.
+    assert_eq!(svo.get_block(VoxelCoord::new(0, 0, 0, 0)), None);
+    assert_eq!(svo.get_block(VoxelCoord::new(1, 0, 1, 1)), Some(VoxelData::new(1)));
+    assert_eq!(svo.get_block(VoxelCoord::new(7, 3, 0, 3)), Some(VoxelData::new(1)));
+    assert_eq!(svo.get_block(VoxelCoord::new(7, 4, 0, 3)), Some(VoxelData::new(0)));
+
+    svo.set_block_at(VoxelCoord::new(6, 1, 2, 3), VoxelData::new(2));
+    assert_eq!(svo.get_block(VoxelCoord::new(6, 1, 2, 3)), Some(VoxelData::new(2)));
+    assert_eq!(svo.get_block(VoxelCoord::new(7, 1, 2, 3)), Some(VoxelData::new(1)));
+    assert_eq!(svo.get_block(VoxelCoord::new(3, 0, 1, 2)), None);
+
+    let mut expected = SVO::floor();
+    expected.set_block(&[1, 5, 2], VoxelData::new(2));
+    assert_eq!(svo, expected);
+}
+
+#[test]
+fn deep_index() {
+    let path = vec![7; 40];
+    assert_eq!(VoxelCoord::from_index(&path[..31]).map(|coord| coord.depth), Some(31));
+    assert_eq!(VoxelCoord::from_index(&path), None);
+
+    // The last voxel along the diagonal ends at the far corner however deep it is.
+    let (origin, side_len) = index_position(&path);
+    assert_approx_eq_eps!(origin + Vector3::new(side_len, side_len, side_len), Vector3::new(1., 1., 1.), 1e-6);
+}
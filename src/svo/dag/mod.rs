@@ -0,0 +1,245 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{Read, Write, Result, Error, ErrorKind};
+use svo::*;
+use svo::save_load::{VOXEL_TAG, OCTANT_TAG};
+
+#[cfg(test)]
+mod test;
+
+// Children refer to other entries in the DAG's node table.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+enum DagNode<T> {
+    Voxel(T),
+    Octants([u32; 8]),
+}
+
+// A sparse voxel DAG: an SVO where every distinct subtree is stored exactly once.
+// Nodes are never changed after they're created, so they can be shared freely. Edits build new
+// nodes along the edited path instead, and the nodes they replace stay around until `compact`.
+#[derive(Debug, Clone)]
+pub struct DagSVO<T = VoxelData> {
+    root: u32,
+    nodes: Vec<DagNode<T>>,
+    lookup: HashMap<DagNode<T>, u32>,
+}
+
+impl<T: LeafData + Eq + Hash> DagSVO<T> {
+    pub fn new_voxel(data: T) -> DagSVO<T> {
+        let mut dag = DagSVO::empty();
+        dag.root = dag.intern(DagNode::Voxel(data));
+        dag
+    }
+
+    fn empty() -> DagSVO<T> {
+        DagSVO { root: 0, nodes: Vec::new(), lookup: HashMap::new() }
+    }
+
+    pub fn from_svo(svo: &SVO<T>) -> DagSVO<T> {
+        let mut dag = DagSVO::empty();
+        dag.root = dag.intern_svo(svo);
+        dag
+    }
+
+    fn intern_svo(&mut self, svo: &SVO<T>) -> u32 {
+        match *svo {
+            SVO::Voxel { data, .. } => self.intern(DagNode::Voxel(data)),
+            SVO::Octants(ref octants) => {
+                let mut children = [0; 8];
+                for ix in 0..8 {
+                    children[ix] = self.intern_svo(&octants[ix]);
+                }
+                self.intern(DagNode::Octants(children))
+            }
+        }
+    }
+
+    pub fn to_svo(&self) -> SVO<T> {
+        self.node_to_svo(self.root)
+    }
+
+    fn node_to_svo(&self, node: u32) -> SVO<T> {
+        match self.nodes[node as usize] {
+            DagNode::Voxel(data) => SVO::new_voxel(data),
+            DagNode::Octants(children) => SVO::new_octants(|ix| self.node_to_svo(children[ix as usize])),
+        }
+    }
+
+    // Return the id of the existing copy of this node, or add it if it's new.
+    fn intern(&mut self, node: DagNode<T>) -> u32 {
+        if let Some(&id) = self.lookup.get(&node) {
+            return id;
+        }
+        let id = self.nodes.len() as u32;
+        self.nodes.push(node);
+        self.lookup.insert(node, id);
+        id
+    }
+
+    // Identical subtrees share an id, so eight equal voxel children recombine into that voxel.
+    fn intern_octants(&mut self, children: [u32; 8]) -> u32 {
+        let first = children[0];
+        if children.iter().all(|&child| child == first) {
+            if let DagNode::Voxel(_) = self.nodes[first as usize] {
+                return first;
+            }
+        }
+        self.intern(DagNode::Octants(children))
+    }
+
+    // The number of distinct subtrees stored, including any left over from edits.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // The data of the voxel containing the end of the index, which may be bigger than the index's
+    // voxel, or None if the index ends at a subdivided node. See PersistentSVO::get.
+    pub fn get_block(&self, index: &[u8]) -> Option<T> {
+        let mut node = self.root;
+        for &ix in index {
+            match self.nodes[node as usize] {
+                DagNode::Voxel(data) => return Some(data),
+                DagNode::Octants(children) => node = children[ix as usize],
+            }
+        }
+        match self.nodes[node as usize] {
+            DagNode::Voxel(data) => Some(data),
+            DagNode::Octants(_) => None,
+        }
+    }
+
+    // Copy-on-write: every node on the path is rebuilt rather than modified, so subtrees
+    // shared with other parts of the tree are left untouched.
+    pub fn set_block(&mut self, index: &[u8], new_data: T) {
+        let root = self.root;
+        self.root = self.set_voxel_from(root, index, new_data);
+    }
+
+    fn set_voxel_from(&mut self, node: u32, index: &[u8], new_data: T) -> u32 {
+        let current = self.nodes[node as usize];
+        if current == DagNode::Voxel(new_data) { return node; } // nothing to do
+
+        match index.split_first() {
+            None => self.intern(DagNode::Voxel(new_data)),
+            Some((&ix, rest)) => {
+                // A voxel splits into eight references to itself.
+                let mut children = match current {
+                    DagNode::Voxel(_) => [node; 8],
+                    DagNode::Octants(children) => children,
+                };
+                children[ix as usize] = self.set_voxel_from(children[ix as usize], rest, new_data);
+                self.intern_octants(children)
+            }
+        }
+    }
+
+    // Throw away every node that's no longer reachable from the root.
+    pub fn compact(&mut self) {
+        let mut compacted = DagSVO::empty();
+        for node in self.reachable_nodes() {
+            compacted.root = compacted.intern(node);
+        }
+        *self = compacted;
+    }
+
+    // Every node reachable from the root, with children before their parents and the root last.
+    // Children are renumbered to their position in the returned list.
+    fn reachable_nodes(&self) -> Vec<DagNode<T>> {
+        let mut reachable = Vec::new();
+        let mut remap = HashMap::new();
+        self.collect_reachable(self.root, &mut reachable, &mut remap);
+        reachable
+    }
+
+    fn collect_reachable(&self, node: u32, reachable: &mut Vec<DagNode<T>>, remap: &mut HashMap<u32, u32>) -> u32 {
+        if let Some(&id) = remap.get(&node) {
+            return id;
+        }
+        let new_node = match self.nodes[node as usize] {
+            DagNode::Voxel(data) => DagNode::Voxel(data),
+            DagNode::Octants(children) => {
+                let mut new_children = [0; 8];
+                for ix in 0..8 {
+                    new_children[ix] = self.collect_reachable(children[ix], reachable, remap);
+                }
+                DagNode::Octants(new_children)
+            }
+        };
+        let id = reachable.len() as u32;
+        reachable.push(new_node);
+        remap.insert(node, id);
+        id
+    }
+
+    // The shared form: a node count, followed by every reachable node with children written
+    // before their parents. Octants refer to their children by position in that list, and the
+    // root is the last node.
+    pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()> {
+        let nodes = self.reachable_nodes();
+        try!{ writer.write_u32::<LittleEndian>(nodes.len() as u32) };
+        for node in nodes {
+            match node {
+                DagNode::Voxel(data) => {
+                    try!{ writer.write_all(&[VOXEL_TAG]) };
+                    try!{ data.write_to(writer) };
+                },
+                DagNode::Octants(children) => {
+                    try!{ writer.write_all(&[OCTANT_TAG]) };
+                    for &child in &children {
+                        try!{ writer.write_u32::<LittleEndian>(child) };
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read + ?Sized>(reader: &mut R) -> Result<DagSVO<T>> {
+        let node_count = try!{ reader.read_u32::<LittleEndian>() };
+        if node_count == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "A DAG needs at least one node."));
+        }
+
+        // Map from position in the file to id in the DAG, which can differ if the file has duplicates.
+        let mut dag = DagSVO::empty();
+        // The count hasn't been checked against the file yet, so it mustn't decide the allocation.
+        let mut ids = Vec::new();
+        for position in 0..node_count {
+            let tag = match reader.read_u8() {
+                Ok(tag) => tag,
+                Err(ref e) if e.kind() == ErrorKind::UnexpectedEof => {
+                    let msg = format!("The DAG should have {} nodes but the file ends after {}", node_count, position);
+                    return Err(Error::new(ErrorKind::InvalidData, msg));
+                },
+                Err(e) => return Err(e),
+            };
+            let id = match tag {
+                VOXEL_TAG => {
+                    let data = try!{ T::read_from(reader) };
+                    dag.intern(DagNode::Voxel(data))
+                },
+                OCTANT_TAG => {
+                    let mut children = [0; 8];
+                    for child in &mut children {
+                        let child_position = try!{ reader.read_u32::<LittleEndian>() };
+                        if child_position >= position {
+                            let msg = format!("Node {} refers to node {} before it's been read", position, child_position);
+                            return Err(Error::new(ErrorKind::InvalidData, msg));
+                        }
+                        *child = ids[child_position as usize];
+                    }
+                    // Files don't have to be recombined, so this keeps the DAG canonical.
+                    dag.intern_octants(children)
+                },
+                other => {
+                    let msg = format!("Invalid SVO type specifier '{}' found", other);
+                    return Err(Error::new(ErrorKind::InvalidData, msg));
+                }
+            };
+            ids.push(id);
+            dag.root = id;
+        }
+        Ok(dag)
+    }
+}
@@ -0,0 +1,122 @@
+use quickcheck::*;
+use std::io::Cursor;
+use svo::*;
+use svo::save_load::{ReadSVO, WriteSVO};
+
+fn sanitise_path(path: Vec<u8>) -> Vec<u8> {
+    path.into_iter().take(4).map(|ix| ix % 8).collect()
+}
+
+#[test]
+fn svo_round_trip() {
+    fn check_svo_round_trip(svo: SVO) -> bool {
+        DagSVO::from_svo(&svo).to_svo() == svo
+    }
+    quickcheck(check_svo_round_trip as fn(SVO) -> bool)
+}
+
+#[test]
+fn identical_subtrees_are_shared() {
+    // Every octant is the same floor, so there's only one copy of it.
+    let svo = SVO::new_octants(|_| SVO::floor());
+    let dag = DagSVO::from_svo(&svo);
+
+    // air, ground, one floor and the root.
+    assert_eq!(dag.node_count(), 4);
+    assert_eq!(dag.to_svo(), svo);
+}
+
+#[test]
+fn set_block_copies_on_write() {
+    let svo = SVO::new_octants(|_| SVO::floor());
+    let mut dag = DagSVO::from_svo(&svo);
+    dag.set_block(&[3, 0], VoxelData::new(2));
+
+    // Only the edited copy of the shared floor changes.
+    assert_eq!(dag.get_block(&[3, 0]), Some(VoxelData::new(2)));
+    assert_eq!(dag.get_block(&[4, 0]), Some(VoxelData::new(1)));
+    let mut expected = svo;
+    expected.set_block(&[3, 0], VoxelData::new(2));
+    assert_eq!(dag.to_svo(), expected);
+}
+
+#[test]
+fn get_block_at_subdivided_node() {
+    let dag = DagSVO::from_svo(&SVO::new_octants(|_| SVO::floor()));
+    assert_eq!(dag.get_block(&[]), None);
+    assert_eq!(dag.get_block(&[2]), None);
+    assert_eq!(dag.get_block(&[2, 1]), Some(VoxelData::new(1)));
+    // Paths that go past a voxel give the voxel's data.
+    assert_eq!(dag.get_block(&[2, 1, 5]), Some(VoxelData::new(1)));
+}
+
+#[test]
+fn set_block_matches_svo() {
+    fn check_set_block_matches_svo(mut svo: SVO, edits: Vec<(Vec<u8>, bool)>) -> bool {
+        let mut dag = DagSVO::from_svo(&svo);
+        for (path, solid) in edits {
+            let path = sanitise_path(path);
+            let data = VoxelData::new(solid as i32);
+            svo.set_block(&path, data);
+            dag.set_block(&path, data);
+        }
+        dag.to_svo() == svo
+    }
+    quickcheck(check_set_block_matches_svo as fn(SVO, Vec<(Vec<u8>, bool)>) -> bool)
+}
+
+#[test]
+fn compact_removes_replaced_nodes() {
+    let mut dag = DagSVO::from_svo(&SVO::floor());
+    dag.set_block(&[0, 1, 2], VoxelData::new(5));
+    dag.set_block(&[0, 1, 2], VoxelData::new(1));
+    assert!(dag.node_count() > 3);
+
+    dag.compact();
+    assert_eq!(dag.node_count(), 3);
+    assert_eq!(dag.to_svo(), SVO::floor());
+}
+
+#[test]
+fn save_load_shared() {
+    let svo = SVO::new_octants(|i| SVO::new_octants(|j| if (i + j) % 2 == 0 { SVO::floor() } else { SVO::example() }));
+    let dag = DagSVO::from_svo(&svo);
+
+    let mut dag_bytes: Vec<u8> = vec![];
+    dag_bytes.write_dag_svo(&dag).unwrap();
+    let mut svo_bytes: Vec<u8> = vec![];
+    svo_bytes.write_svo(&svo).unwrap();
+    assert!(dag_bytes.len() < svo_bytes.len());
+
+    let loaded: DagSVO = Cursor::new(dag_bytes).read_dag_svo().unwrap();
+    assert_eq!(loaded.node_count(), dag.node_count());
+    assert_eq!(loaded.to_svo(), svo);
+}
+
+#[test]
+fn load_forward_reference() {
+    // One octant node that refers to itself.
+    let mut bytes = vec![1, 0, 0, 0, 2];
+    bytes.extend_from_slice(&[0; 32]);
+    let result: ::std::io::Result<DagSVO> = Cursor::new(bytes).read_dag_svo();
+    assert!(result.is_err());
+}
+
+#[test]
+fn load_huge_node_count() {
+    // A header claiming about 4G nodes, followed by a single voxel.
+    let bytes = vec![0xFF, 0xFF, 0xFF, 0xFF, 1, 0, 0, 0, 0];
+    let result: ::std::io::Result<DagSVO> = Cursor::new(bytes).read_dag_svo();
+    assert_eq!(result.unwrap_err().kind(), ::std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn load_recombines_octants() {
+    // A voxel, and an octant node whose children are all that voxel.
+    let mut bytes = vec![2, 0, 0, 0, 1, 3, 0, 0, 0, 2];
+    bytes.extend_from_slice(&[0; 32]);
+    let loaded: DagSVO = Cursor::new(bytes).read_dag_svo().unwrap();
+    assert_eq!(loaded.node_count(), 1);
+    assert_eq!(loaded.get_block(&[]), Some(VoxelData::new(3)));
+    assert_eq!(loaded.to_svo(), SVO::new_voxel(VoxelData::new(3)));
+}
@@ -0,0 +1,169 @@
+use nalgebra::{Vector3, Norm};
+use std::f32;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// Squared distances in cells, standing in for infinity so the distance transform doesn't have to
+// subtract one infinity from another.
+const FAR: f32 = 1e20;
+
+// A signed distance field sampled at the centres of a resolution^3 grid over the SVO's unit cube,
+// with the same axes as cast_ray. Distances are negative inside non-empty voxels and positive
+// outside of them, and are measured in the unit cube's units. With nothing solid at all every
+// sample is infinite, and with nothing empty every sample is minus infinity.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DistanceField {
+    resolution: u32,
+    samples: Vec<f32>,
+}
+
+impl DistanceField {
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub fn cell_len(&self) -> f32 {
+        1.0 / self.resolution as f32
+    }
+
+    // The sample at the centre of the cell (x, y, z).
+    pub fn sample(&self, x: u32, y: u32, z: u32) -> f32 {
+        self.samples[self.cell(x, y, z)]
+    }
+
+    fn cell(&self, x: u32, y: u32, z: u32) -> usize {
+        let n = self.resolution as usize;
+        x as usize + n * (y as usize + n * z as usize)
+    }
+
+    // The distance from a point to the nearest surface, interpolated between the nearest samples.
+    // Between the outermost sample centres and the cube's faces the outermost samples are used,
+    // and points outside of the unit cube get the distance to the cube added on.
+    pub fn distance_at(&self, point: Vector3<f32>) -> f32 {
+        let clamped = Vector3::new(clamp_unit(point.x), clamp_unit(point.y), clamp_unit(point.z));
+        let outside = (point - clamped).norm();
+
+        // The cells whose centres are either side of the point, and how far it is between them.
+        let n = self.resolution;
+        let lower = |f: f32| {
+            let cell_f = (f * n as f32 - 0.5).max(0.0).min((n - 1) as f32);
+            let cell = cell_f.floor() as u32;
+            let upper = (cell + 1).min(n - 1);
+            (cell, upper, cell_f - cell as f32)
+        };
+        let (x0, x1, tx) = lower(clamped.x);
+        let (y0, y1, ty) = lower(clamped.y);
+        let (z0, z1, tz) = lower(clamped.z);
+
+        let lerp = |a: f32, b: f32, t: f32| if t == 0.0 { a } else { a + (b - a) * t };
+        let along_x = |y, z| lerp(self.sample(x0, y, z), self.sample(x1, y, z), tx);
+        let along_y = |z| lerp(along_x(y0, z), along_x(y1, z), ty);
+        lerp(along_y(z0), along_y(z1), tz) + outside
+    }
+}
+
+fn clamp_unit(f: f32) -> f32 {
+    f.max(0.0).min(1.0)
+}
+
+impl<T: LeafData> SVO<T> {
+    // Sample the signed distance to the surface of the non-empty voxels at every cell centre.
+    // A voxel smaller than a cell only counts if it contains the cell's centre.
+    pub fn distance_field(&self, resolution: u32) -> DistanceField {
+        assert!(resolution > 0, "A distance field needs at least one sample");
+        let n = resolution as usize;
+        let cell_len = 1.0 / resolution as f32;
+        let mut solid = Vec::with_capacity(n * n * n);
+        for z in 0..n {
+            for y in 0..n {
+                for x in 0..n {
+                    let centre = (Vector3::new(x as f32, y as f32, z as f32) + Vector3::new(0.5, 0.5, 0.5)) * cell_len;
+                    solid.push(self.voxel_at(centre).map_or(false, |(data, _)| !data.is_empty()));
+                }
+            }
+        }
+
+        // The distances between cell centres to the nearest cell of the other kind. The surface
+        // is half a cell beyond the last centre on either side of it.
+        let to_solid = distance_transform(&solid, n, true);
+        let to_empty = distance_transform(&solid, n, false);
+        let samples = solid.iter().zip(to_solid.iter().zip(to_empty.iter())).map(|(&is_solid, (&out, &inside))| {
+            if is_solid { -signed_len(inside, cell_len) } else { signed_len(out, cell_len) }
+        }).collect();
+
+        DistanceField { resolution: resolution, samples: samples }
+    }
+
+    // The exact signed distance from the point to the surface of the non-empty voxels, found by
+    // checking every leaf of the other kind. Use a DistanceField for many queries.
+    pub fn distance_at(&self, point: Vector3<f32>) -> f32 {
+        let inside = self.voxel_at(point).map_or(false, |(data, _)| !data.is_empty());
+        let nearest = self.leaves()
+            .filter(|leaf| leaf.data.is_empty() == inside)
+            .map(|leaf| distance_to_box(point, leaf.origin, leaf.side_len))
+            .fold(f32::INFINITY, f32::min);
+        if inside { -nearest } else { nearest }
+    }
+}
+
+fn signed_len(squared_cells: f32, cell_len: f32) -> f32 {
+    if squared_cells >= FAR { f32::INFINITY } else { (squared_cells.sqrt() - 0.5) * cell_len }
+}
+
+fn distance_to_box(point: Vector3<f32>, origin: Vector3<f32>, side_len: f32) -> f32 {
+    let outside = |p: f32, lo: f32| (lo - p).max(0.0).max(p - (lo + side_len));
+    Vector3::new(outside(point.x, origin.x), outside(point.y, origin.y), outside(point.z, origin.z)).norm()
+}
+
+// The squared distance in cells from each cell to the nearest cell where solid == target, using
+// the separable transform of Felzenszwalb and Huttenlocher along each axis in turn.
+fn distance_transform(solid: &[bool], n: usize, target: bool) -> Vec<f32> {
+    let mut grid: Vec<f32> = solid.iter().map(|&s| if s == target { 0.0 } else { FAR }).collect();
+    let mut line = vec![0.0; n];
+    let mut out = vec![0.0; n];
+    for &stride in &[1, n, n * n] {
+        for start in 0..n * n * n {
+            // Each line along this axis starts where the axis coordinate is zero.
+            if (start / stride) % n != 0 { continue; }
+            for i in 0..n { line[i] = grid[start + i * stride]; }
+            distance_transform_line(&line, &mut out);
+            for i in 0..n { grid[start + i * stride] = out[i]; }
+        }
+    }
+    grid
+}
+
+// One dimensional squared distance transform: out[q] = min over p of (q - p)^2 + f[p].
+fn distance_transform_line(f: &[f32], out: &mut [f32]) {
+    let n = f.len();
+    // The parabolas making up the lower envelope, and where each one takes over from the last.
+    let mut vertices = vec![0; n];
+    let mut bounds = vec![0.0; n + 1];
+    let mut k = 0;
+    bounds[0] = -f32::INFINITY;
+    bounds[1] = f32::INFINITY;
+    for q in 1..n {
+        let intersection = |p: usize| ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2 * (q - p)) as f32;
+        // Drop the parabolas that the new one is lower than everywhere they were in the envelope.
+        // The first one always stays, since it takes over from minus infinity.
+        let mut s = intersection(vertices[k]);
+        while s <= bounds[k] {
+            k -= 1;
+            s = intersection(vertices[k]);
+        }
+        k += 1;
+        vertices[k] = q;
+        bounds[k] = s;
+        bounds[k + 1] = f32::INFINITY;
+    }
+
+    let mut k = 0;
+    for q in 0..n {
+        while bounds[k + 1] < q as f32 { k += 1; }
+        let p = vertices[k];
+        let d = q as f32 - p as f32;
+        out[q] = d * d + f[p];
+    }
+}
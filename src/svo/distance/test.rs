@@ -0,0 +1,80 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use std::f32;
+use svo::*;
+
+fn close(a: f32, b: f32, eps: f32) -> bool {
+    (a - b).abs() <= eps
+}
+
+#[test]
+fn floor_distances() {
+    // The floor's surface is the plane y = 0.5.
+    let svo = SVO::floor();
+    assert!(close(svo.distance_at(Vector3::new(0.5, 0.75, 0.5)), 0.25, 1e-6));
+    assert!(close(svo.distance_at(Vector3::new(0.1, 0.2, 0.9)), -0.3, 1e-6));
+
+    let field = svo.distance_field(8);
+    for y in 0..8 {
+        let expected = (y as f32 + 0.5) / 8.0 - 0.5;
+        assert!(close(field.sample(3, y, 5), expected, 1e-6), "y = {}: {}", y, field.sample(3, y, 5));
+    }
+    assert!(close(field.distance_at(Vector3::new(0.3, 0.7, 0.6)), 0.2, 1e-6));
+    assert!(close(field.distance_at(Vector3::new(0.3, 0.4, 0.6)), -0.1, 1e-6));
+}
+
+#[test]
+fn outside_the_cube() {
+    // Below the lowest sample centre the field stays at that sample, 1.5 cells inside the floor.
+    let field = SVO::floor().distance_field(4);
+    assert!(close(field.distance_at(Vector3::new(0.5, 0.0, -2.0)), -0.375 + 2.0, 1e-6));
+}
+
+#[test]
+fn nothing_solid() {
+    let svo = SVO::new_voxel(VoxelData::new(0));
+    assert_eq!(svo.distance_at(Vector3::new(0.5, 0.5, 0.5)), f32::INFINITY);
+    assert_eq!(svo.distance_field(4).sample(1, 2, 3), f32::INFINITY);
+
+    let svo = SVO::new_voxel(VoxelData::new(1));
+    assert_eq!(svo.distance_field(4).sample(1, 2, 3), -f32::INFINITY);
+}
+
+#[test]
+fn single_voxel_corners() {
+    // One solid voxel in the corner at the origin, two cells along each side, and the rest empty.
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[0, 0], VoxelData::new(1));
+    let field = svo.distance_field(8);
+    assert!(close(field.sample(0, 0, 0), -0.1875, 1e-6));
+    assert!(close(field.sample(1, 1, 1), -0.0625, 1e-6));
+    // Diagonally across from it, the nearest solid centre is three cells away along each axis.
+    let expected = ((3.0f32 * 9.0).sqrt() - 0.5) / 8.0;
+    assert!(close(field.sample(4, 4, 4), expected, 1e-6));
+}
+
+#[test]
+fn field_matches_brute_force() {
+    // Check the distance transform against comparing every pair of cell centres.
+    fn check_field(svo: SVO) -> bool {
+        let n = 4;
+        let field = svo.distance_field(n);
+        let centre = |x, y, z| Vector3::new(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5) / n as f32;
+        let solid = |x, y, z| !svo.voxel_at(centre(x, y, z)).unwrap().0.is_empty();
+        let cells: Vec<_> = (0..n).flat_map(|z| (0..n).flat_map(move |y| (0..n).map(move |x| (x, y, z)))).collect();
+        cells.iter().all(|&(x, y, z)| {
+            let is_solid = solid(x, y, z);
+            let nearest = cells.iter()
+                .filter(|&&(ox, oy, oz)| solid(ox, oy, oz) != is_solid)
+                .map(|&(ox, oy, oz)| {
+                    let d = Vector3::new(ox as f32 - x as f32, oy as f32 - y as f32, oz as f32 - z as f32);
+                    (d.x * d.x + d.y * d.y + d.z * d.z).sqrt()
+                })
+                .fold(f32::INFINITY, f32::min);
+            let expected = if nearest == f32::INFINITY { nearest } else { (nearest - 0.5) / n as f32 };
+            let expected = if is_solid { -expected } else { expected };
+            expected == field.sample(x, y, z) || close(expected, field.sample(x, y, z), 1e-5)
+        })
+    }
+    quickcheck(check_field as fn(SVO) -> bool);
+}
@@ -0,0 +1,95 @@
+use nalgebra::{Vector3, zero};
+use svo::*;
+use svo::coord::MAX_DEPTH;
+
+#[cfg(test)]
+mod test;
+
+impl<T: LeafData> SVO<T> {
+    // Set every voxel at the given depth that overlaps the box from min to max, using the same
+    // overlap rule as leaves_in_box. Parts of the box outside of the SVO are ignored.
+    pub fn fill_box(&mut self, min: Vector3<f32>, max: Vector3<f32>, depth: u32, new_data: T) {
+        self.fill_box_with(&RegistrationFunctions::blank(), min, max, depth, new_data);
+    }
+
+    // Like fill_box, but every leaf that appears or disappears is registered or deregistered.
+    pub fn fill_box_with(&mut self,
+                         registration: &RegistrationFunctions<T>,
+                         min: Vector3<f32>,
+                         max: Vector3<f32>,
+                         depth: u32,
+                         new_data: T) {
+        assert!(depth <= MAX_DEPTH, "fill_box depth {} is greater than {}", depth, MAX_DEPTH);
+        if max.x < 0. || max.y < 0. || max.z < 0. { return; }
+        if min.x > 1. || min.y > 1. || min.z > 1. { return; }
+
+        // The range of grid cells that the box overlaps, clamped to the SVO. The last cell along
+        // each axis includes the far face of the cube, so a box starting at 1 overlaps it.
+        let grid_size = (1u64 << depth) as f32;
+        let lower = |f: f32| (f * grid_size).floor().max(0.).min(grid_size - 1.) as u64;
+        let upper = |f: f32| (f * grid_size).floor().min(grid_size - 1.) as u64;
+        let lo = (lower(min.x), lower(min.y), lower(min.z));
+        let hi = (upper(max.x), upper(max.y), upper(max.z));
+        self.fill_range(registration, 0, depth, (0, 0, 0), zero(), lo, hi, &new_data);
+    }
+
+    // Set every voxel between the two coordinates, inclusive. Both must be at the same depth.
+    pub fn fill_coords(&mut self, min: VoxelCoord, max: VoxelCoord, new_data: T) {
+        self.fill_coords_with(&RegistrationFunctions::blank(), min, max, new_data);
+    }
+
+    // Like fill_coords, but every leaf that appears or disappears is registered or deregistered.
+    pub fn fill_coords_with(&mut self, registration: &RegistrationFunctions<T>, min: VoxelCoord, max: VoxelCoord, new_data: T) {
+        assert_eq!(min.depth, max.depth);
+        let lo = (min.x as u64, min.y as u64, min.z as u64);
+        let hi = (max.x as u64, max.y as u64, max.z as u64);
+        self.fill_range(registration, 0, min.depth, (0, 0, 0), zero(), lo, hi, &new_data);
+    }
+
+    // This node covers the grid cells from origin to origin + 2^(target_depth - depth).
+    // Nodes that are entirely inside the range are replaced straight away, and the rest are split
+    // and filled recursively with a single recombine each on the way back up. position is the
+    // same origin in the unit cube, for registration.
+    fn fill_range(&mut self,
+                  registration: &RegistrationFunctions<T>,
+                  depth: u32,
+                  target_depth: u32,
+                  origin: (u64, u64, u64),
+                  position: Vector3<f32>,
+                  lo: (u64, u64, u64),
+                  hi: (u64, u64, u64),
+                  new_data: &T) {
+        let size = 1u64 << (target_depth - depth);
+        let far = (origin.0 + size - 1, origin.1 + size - 1, origin.2 + size - 1);
+
+        let disjoint = far.0 < lo.0 || far.1 < lo.1 || far.2 < lo.2 ||
+                       origin.0 > hi.0 || origin.1 > hi.1 || origin.2 > hi.2;
+        if disjoint { return; }
+
+        let covered = lo.0 <= origin.0 && lo.1 <= origin.1 && lo.2 <= origin.2 &&
+                      far.0 <= hi.0 && far.1 <= hi.1 && far.2 <= hi.2;
+        if self.get_voxel_data() == Some(*new_data) { return; } // nothing to do
+
+        if covered {
+            self.deregister_all(registration);
+            *self = SVO::new_registered_voxel(registration, *new_data, position, depth as i32);
+            return;
+        }
+
+        if self.get_voxel_data().is_some() {
+            self.subdivide_voxel_with(registration, position, depth as i32);
+        }
+
+        if let SVO::Octants(ref mut octants) = *self {
+            let half = size / 2;
+            for ix in 0..8u8 {
+                let child_origin = (origin.0 + half * (ix & 1) as u64,
+                                    origin.1 + half * ((ix >> 1) & 1) as u64,
+                                    origin.2 + half * ((ix >> 2) & 1) as u64);
+                octants[ix as usize].fill_range(registration, depth + 1, target_depth, child_origin,
+                                                position + offset(ix, depth as i32), lo, hi, new_data);
+            }
+        }
+        self.recombine_svo_with(registration, position, depth as i32);
+    }
+}
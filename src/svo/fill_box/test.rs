@@ -0,0 +1,104 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use svo::*;
+
+#[test]
+fn fill_whole_octant() {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.fill_coords(VoxelCoord::new(0, 0, 0, 3), VoxelCoord::new(3, 3, 3, 3), VoxelData::new(1));
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    expected.set_block(&[0], VoxelData::new(1));
+    assert_eq!(svo, expected);
+
+    svo.fill_coords(VoxelCoord::new(0, 0, 0, 1), VoxelCoord::new(1, 1, 1, 1), VoxelData::new(1));
+    assert_eq!(svo, SVO::new_voxel(VoxelData::new(1)));
+}
+
+#[test]
+fn fill_replaces_covered_subtrees() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 3, 4], VoxelData::new(2));
+    svo.fill_box(Vector3::new(0., 0., 0.), Vector3::new(1., 0.4, 1.), 1, VoxelData::new(3));
+
+    let mut expected = SVO::floor();
+    for &ix in &[0, 1, 4, 5] { expected.set_block(&[ix], VoxelData::new(3)); }
+    assert_eq!(svo, expected);
+}
+
+#[test]
+fn fill_box_world_space() {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    // Overlaps cells 1 to 2 along x at depth 2, and just touches cell 2 along y.
+    svo.fill_box(Vector3::new(0.3, 0.25, 0.), Vector3::new(0.6, 0.5, 0.2), 2, VoxelData::new(1));
+
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    for x in 1..3 {
+        for y in 1..3 {
+            expected.set_block_at(VoxelCoord::new(x, y, 0, 2), VoxelData::new(1));
+        }
+    }
+    assert_eq!(svo, expected);
+}
+
+#[test]
+fn fill_box_outside() {
+    let mut svo = SVO::floor();
+    svo.fill_box(Vector3::new(-2., -2., -2.), Vector3::new(-1., 0.5, 0.5), 3, VoxelData::new(2));
+    svo.fill_box(Vector3::new(1.5, 0., 0.), Vector3::new(2., 0.5, 0.5), 3, VoxelData::new(2));
+    assert_eq!(svo, SVO::floor());
+}
+
+#[test]
+fn fill_box_far_faces() {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.fill_box(Vector3::new(1., 0., 0.), Vector3::new(1., 0.2, 0.2), 2, VoxelData::new(1));
+
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    expected.set_block_at(VoxelCoord::new(3, 0, 0, 2), VoxelData::new(1));
+    assert_eq!(svo, expected);
+}
+
+#[test]
+fn fill_box_matches_leaves_in_box() {
+    fn check_fill_box_matches_leaves_in_box(a: (u8, u8, u8), b: (u8, u8, u8)) -> bool {
+        // Corners from -0.25 to 1.25, often on cell boundaries or the faces of the cube.
+        let corner = |(x, y, z): (u8, u8, u8)| Vector3::new((x % 12) as f32, (y % 12) as f32, (z % 12) as f32) / 8. - 0.25;
+        let (a, b) = (corner(a), corner(b));
+        let (min, max) = (Vector3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+                          Vector3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)));
+
+        // Every cell at depth 2 is a leaf of its own, since their data all differs.
+        let grid = SVO::new_octants(|i| SVO::new_octants(|j| SVO::new_voxel(VoxelData::new((i * 8 + j) as i32))));
+        let mut expected = SVO::new_voxel(VoxelData::new(0));
+        for (origin, _, _) in grid.leaves_in_box(min, max) {
+            let cell = origin * 4.;
+            expected.set_block_at(VoxelCoord::new(cell.x as u32, cell.y as u32, cell.z as u32, 2), VoxelData::new(1));
+        }
+
+        let mut svo = SVO::new_voxel(VoxelData::new(0));
+        svo.fill_box(min, max, 2, VoxelData::new(1));
+        svo == expected
+    }
+    quickcheck(check_fill_box_matches_leaves_in_box as fn((u8, u8, u8), (u8, u8, u8)) -> bool)
+}
+
+#[test]
+fn fill_matches_set_block() {
+    fn check_fill_matches_set_block(mut svo: SVO, a: (u8, u8, u8), b: (u8, u8, u8), solid: bool) -> bool {
+        let data = VoxelData::new(solid as i32);
+        let lo = ((a.0 % 8).min(b.0 % 8) as u32, (a.1 % 8).min(b.1 % 8) as u32, (a.2 % 8).min(b.2 % 8) as u32);
+        let hi = ((a.0 % 8).max(b.0 % 8) as u32, (a.1 % 8).max(b.1 % 8) as u32, (a.2 % 8).max(b.2 % 8) as u32);
+
+        let mut expected = svo.clone();
+        for x in lo.0..hi.0 + 1 {
+            for y in lo.1..hi.1 + 1 {
+                for z in lo.2..hi.2 + 1 {
+                    expected.set_block_at(VoxelCoord::new(x, y, z, 3), data);
+                }
+            }
+        }
+        svo.fill_coords(VoxelCoord::new(lo.0, lo.1, lo.2, 3), VoxelCoord::new(hi.0, hi.1, hi.2, 3), data);
+        svo.same_voxels(&expected, 3)
+    }
+    quickcheck(check_fill_matches_set_block as fn(SVO, (u8, u8, u8), (u8, u8, u8), bool) -> bool)
+}
@@ -6,6 +6,20 @@ use std::u8;
 #[cfg(test)]
 mod test;
 
+// What goes below and above the surface.
+#[derive(Debug, PartialEq, Copy, Clone)]
+struct Terrain {
+	air: VoxelData,
+	ground: VoxelData
+}
+
+impl Terrain {
+	fn new(materials: &MaterialRegistry) -> Terrain {
+		let id_of = |name| materials.id_of(name).unwrap_or_else(|| panic!("The height map needs a material called {}", name));
+		Terrain { air: VoxelData::new(id_of("air")), ground: VoxelData::new(id_of("stone")) }
+	}
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 struct SubImage<'a> {
 	image: &'a[u8],
@@ -88,26 +102,44 @@ impl<'a> SubImage<'a> {
 }
 
 impl SVO {
-	pub fn height_map(depth: u32, image: &[u8], width: u32, height: u32) -> SVO {
+	// The ground is the registry's "stone" and the rest is its "air".
+	pub fn height_map(depth: u32, image: &[u8], width: u32, height: u32, materials: &MaterialRegistry) -> SVO {
 		assert_eq!(image.len(), (width * height) as usize);
-		SVO::height_map_sub(depth, SubImage::new(image, width, height))
+		SVO::height_map_sub(depth, SubImage::new(image, width, height), Terrain::new(materials))
 	}
 
-	fn height_map_sub(depth: u32, image: SubImage) -> SVO {
+	fn height_map_sub(depth: u32, image: SubImage, terrain: Terrain) -> SVO {
 		match image.octs() {
 			Some(sub_images) if depth > 0 => { // Recurse
 				let mut svo = SVO::new_octants(|ix| {
-					SVO::height_map_sub(depth-1, sub_images[ix as usize])
+					SVO::height_map_sub(depth-1, sub_images[ix as usize], terrain)
 				});
 				svo.recombine_svo();
 				svo
 			},
 
-			_ => { // Make a voxel here
-				let threshold = image.b_0 + (image.b_n - image.b_0) / 2;
-				let voxel_type = if image.byte_avg() <= threshold { 0 } else { 1 };
-				SVO::new_voxel(VoxelData::new(voxel_type))
-			}
+			_ => SVO::height_map_voxel(image, terrain) // Make a voxel here
+		}
+	}
+
+	// The same as height_map, but building the subtrees on up to the given number of threads.
+	pub fn height_map_parallel(depth: u32, image: &[u8], width: u32, height: u32, materials: &MaterialRegistry, threads: usize) -> SVO {
+		assert_eq!(image.len(), (width * height) as usize);
+		SVO::height_map_sub_parallel(depth, SubImage::new(image, width, height), Terrain::new(materials), threads)
+	}
+
+	fn height_map_sub_parallel(depth: u32, image: SubImage, terrain: Terrain, threads: usize) -> SVO {
+		if threads <= 1 { return SVO::height_map_sub(depth, image, terrain); }
+		match image.octs() {
+			Some(sub_images) if depth > 0 => SVO::from_parallel_octants(threads, |ix, octant_threads| {
+				SVO::height_map_sub_parallel(depth-1, sub_images[ix as usize], terrain, octant_threads)
+			}),
+			_ => SVO::height_map_voxel(image, terrain)
 		}
 	}
-}
\ No newline at end of file
+
+	fn height_map_voxel(image: SubImage, terrain: Terrain) -> SVO {
+		let threshold = image.b_0 + (image.b_n - image.b_0) / 2;
+		SVO::new_voxel(if image.byte_avg() <= threshold { terrain.air } else { terrain.ground })
+	}
+}
@@ -7,7 +7,7 @@ fn flat_height_map() {
     let height = 4;
 
     let image: [u8; 16] = [127u8; 16];
-    let svo = SVO::height_map(1, &image, width, height);
+    let svo = SVO::height_map(1, &image, width, height, &MaterialRegistry::default());
     svo.assert_contains(vec![
         (0. , 0. , 0. , 1, 1),
         (0.5, 0. , 0. , 1, 1),
@@ -26,7 +26,7 @@ fn full_height_map() {
     let height = 4;
 
     let image: [u8; 16] = [u8::MAX; 16];
-    let svo = SVO::height_map(1, &image, width, height);
+    let svo = SVO::height_map(1, &image, width, height, &MaterialRegistry::default());
     svo.assert_contains(vec![(0., 0., 0., 0, 1)]);
 }
 
@@ -36,6 +36,25 @@ fn empty_height_map() {
     let height = 4;
 
     let image: [u8; 16] = [0u8; 16];
-    let svo = SVO::height_map(1, &image, width, height);
+    let svo = SVO::height_map(1, &image, width, height, &MaterialRegistry::default());
     svo.assert_contains(vec![(0., 0., 0., 0, 0)]);
-}
\ No newline at end of file
+}
+#[test]
+fn height_map_materials_come_from_the_registry() {
+    let materials = MaterialRegistry::parse("
+        3 stone 808080FF solid
+        7 air 00000000 transparent
+    ").unwrap();
+    let image: [u8; 16] = [127u8; 16];
+    let svo = SVO::height_map(1, &image, 4, 4, &materials);
+    svo.assert_contains(vec![
+        (0. , 0. , 0. , 1, 3),
+        (0.5, 0. , 0. , 1, 3),
+        (0. , 0.5, 0. , 1, 7),
+        (0.5, 0.5, 0. , 1, 7),
+        (0. , 0. , 0.5, 1, 3),
+        (0.5, 0. , 0.5, 1, 3),
+        (0. , 0.5, 0.5, 1, 7),
+        (0.5, 0.5, 0.5, 1, 7)
+    ]);
+}
@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::mem::size_of;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// One edit: the subtree at the index path before and after it was changed.
+struct Edit<T> {
+    index: Vec<u8>,
+    before: SVO<T>,
+    after: SVO<T>,
+}
+
+// Edits that are undone and redone together.
+struct Transaction<T> {
+    edits: Vec<Edit<T>>,
+    bytes: usize,
+}
+
+impl<T> Transaction<T> {
+    fn new() -> Transaction<T> {
+        Transaction { edits: Vec::new(), bytes: 0 }
+    }
+}
+
+// Records edits to an SVO so that they can be undone and redone. Only the subtrees that an edit
+// replaced are kept, and the oldest transactions are forgotten once they use more than max_bytes.
+// A transaction that's bigger than max_bytes on its own can't be kept at all, so recording it
+// returns false and forgets the whole history, since the older edits can't be undone past it.
+pub struct EditHistory<T = VoxelData> {
+    undo: VecDeque<Transaction<T>>,
+    redo: Vec<Transaction<T>>,
+    open: Option<Transaction<T>>,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl<T: LeafData> EditHistory<T> {
+    pub fn new(max_bytes: usize) -> EditHistory<T> {
+        EditHistory { undo: VecDeque::new(), redo: Vec::new(), open: None, bytes: 0, max_bytes: max_bytes }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // The estimated memory used by the recorded subtrees.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.forget_oldest();
+    }
+
+    // Group every edit until the matching commit into one transaction.
+    pub fn begin(&mut self) {
+        assert!(self.open.is_none(), "EditHistory::begin called inside a transaction");
+        self.open = Some(Transaction::new());
+    }
+
+    // Returns false if the transaction was too big to keep.
+    pub fn commit(&mut self) -> bool {
+        let transaction = self.open.take().expect("EditHistory::commit called outside of a transaction");
+        self.push(transaction)
+    }
+
+    pub fn set_block(&mut self, svo: &mut SVO<T>, index: &[u8], new_data: T) -> bool {
+        self.record(svo, index, |svo| svo.set_block(index, new_data))
+    }
+
+    // Apply an edit that only changes the subtree at the index path, and record it.
+    // Edits that can change anything, like fill_box or apply_brush, should use the empty path.
+    // Returns false if the edit was too big to keep. Inside a transaction that's decided by commit.
+    pub fn record<F>(&mut self, svo: &mut SVO<T>, index: &[u8], edit: F) -> bool
+            where F: FnOnce(&mut SVO<T>) {
+        let before = svo.extract(index);
+        edit(svo);
+        let after = svo.extract(index);
+        if before == after { return true; }
+
+        let edit = Edit { index: index.to_vec(), before: before, after: after };
+        let edit_bytes = subtree_bytes(&edit.before) + subtree_bytes(&edit.after);
+        match self.open {
+            Some(ref mut transaction) => {
+                transaction.edits.push(edit);
+                transaction.bytes += edit_bytes;
+                true
+            }
+            None => self.push(Transaction { edits: vec![edit], bytes: edit_bytes }),
+        }
+    }
+
+    // Undo the latest transaction, returning false if there's nothing to undo.
+    pub fn undo(&mut self, svo: &mut SVO<T>) -> bool {
+        assert!(self.open.is_none(), "EditHistory::undo called inside a transaction");
+        let transaction = match self.undo.pop_back() {
+            Some(transaction) => transaction,
+            None => return false,
+        };
+        for edit in transaction.edits.iter().rev() {
+            svo.stamp(&edit.index, &edit.before, StampMode::Replace);
+        }
+        self.redo.push(transaction);
+        true
+    }
+
+    // Redo the latest undone transaction, returning false if there's nothing to redo.
+    pub fn redo(&mut self, svo: &mut SVO<T>) -> bool {
+        assert!(self.open.is_none(), "EditHistory::redo called inside a transaction");
+        let transaction = match self.redo.pop() {
+            Some(transaction) => transaction,
+            None => return false,
+        };
+        for edit in &transaction.edits {
+            svo.stamp(&edit.index, &edit.after, StampMode::Replace);
+        }
+        self.undo.push_back(transaction);
+        true
+    }
+
+    fn push(&mut self, transaction: Transaction<T>) -> bool {
+        if transaction.edits.is_empty() { return true; }
+        // A new edit replaces whatever could have been redone.
+        for old in self.redo.drain(..) {
+            self.bytes -= old.bytes;
+        }
+        if transaction.bytes > self.max_bytes {
+            warn!("Forgetting the edit history: an edit needs {} bytes but the limit is {}", transaction.bytes, self.max_bytes);
+            self.undo.clear();
+            self.bytes = 0;
+            return false;
+        }
+        self.bytes += transaction.bytes;
+        self.undo.push_back(transaction);
+        self.forget_oldest();
+        true
+    }
+
+    fn forget_oldest(&mut self) {
+        while self.bytes > self.max_bytes {
+            match self.undo.pop_front() {
+                Some(oldest) => self.bytes -= oldest.bytes,
+                None => break,
+            }
+        }
+    }
+}
+
+fn subtree_bytes<T: LeafData>(svo: &SVO<T>) -> usize {
+    match *svo {
+        SVO::Voxel { .. } => size_of::<SVO<T>>(),
+        SVO::Octants(ref octants) => size_of::<SVO<T>>() + octants.iter().map(|octant| subtree_bytes(octant)).sum::<usize>(),
+    }
+}
@@ -0,0 +1,136 @@
+use quickcheck::*;
+use std::mem::size_of;
+use svo::*;
+
+#[test]
+fn undo_and_redo() {
+    let mut svo = SVO::floor();
+    let mut history = EditHistory::new(1 << 20);
+    assert!(!history.undo(&mut svo));
+
+    history.set_block(&mut svo, &[2], VoxelData::new(1));
+    history.set_block(&mut svo, &[1, 4], VoxelData::new(3));
+    let edited = svo.clone();
+
+    assert!(history.undo(&mut svo));
+    let mut expected = SVO::floor();
+    expected.set_block(&[2], VoxelData::new(1));
+    assert_eq!(svo, expected);
+    assert!(history.undo(&mut svo));
+    assert_eq!(svo, SVO::floor());
+    assert!(!history.can_undo());
+
+    assert!(history.redo(&mut svo));
+    assert!(history.redo(&mut svo));
+    assert_eq!(svo, edited);
+    assert!(!history.redo(&mut svo));
+}
+
+#[test]
+fn undo_recombined_edit() {
+    // Filling in the last empty octant recombines the whole tree, which undo has to split again.
+    let mut svo = SVO::new_voxel(VoxelData::new(1));
+    svo.set_block(&[5, 5], VoxelData::new(0));
+    let original = svo.clone();
+    let mut history = EditHistory::new(1 << 20);
+    history.set_block(&mut svo, &[5, 5], VoxelData::new(1));
+    assert_eq!(svo, SVO::new_voxel(VoxelData::new(1)));
+    history.undo(&mut svo);
+    assert_eq!(svo, original);
+}
+
+#[test]
+fn transactions() {
+    let mut svo = SVO::floor();
+    let mut history = EditHistory::new(1 << 20);
+    history.begin();
+    history.set_block(&mut svo, &[2], VoxelData::new(1));
+    history.set_block(&mut svo, &[2, 3], VoxelData::new(4));
+    history.record(&mut svo, &[], |svo| svo.fill_coords(VoxelCoord::new(0, 0, 0, 2), VoxelCoord::new(1, 1, 1, 2), VoxelData::new(5)));
+    history.commit();
+    let edited = svo.clone();
+
+    history.undo(&mut svo);
+    assert_eq!(svo, SVO::floor());
+    assert!(!history.can_undo());
+    history.redo(&mut svo);
+    assert_eq!(svo, edited);
+}
+
+#[test]
+fn new_edit_clears_redo() {
+    let mut svo = SVO::floor();
+    let mut history = EditHistory::new(1 << 20);
+    history.set_block(&mut svo, &[2], VoxelData::new(1));
+    history.undo(&mut svo);
+    assert!(history.can_redo());
+    history.set_block(&mut svo, &[3], VoxelData::new(1));
+    assert!(!history.can_redo());
+
+    // Edits that don't change anything aren't recorded.
+    history.set_block(&mut svo, &[0], VoxelData::new(1));
+    history.undo(&mut svo);
+    assert_eq!(svo, SVO::floor());
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn memory_cap_forgets_oldest() {
+    // Each of these edits stores two single voxels.
+    let edit_bytes = 2 * size_of::<SVO>();
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    let mut history = EditHistory::new(2 * edit_bytes);
+    for ix in 0..3 {
+        history.set_block(&mut svo, &[ix, 0], VoxelData::new(1));
+    }
+    assert_eq!(history.bytes(), 2 * edit_bytes);
+    assert!(history.undo(&mut svo));
+    assert!(history.undo(&mut svo));
+    assert!(!history.undo(&mut svo));
+    assert_eq!(svo.get_block(VoxelCoord::new(0, 0, 0, 2)), Some(VoxelData::new(1)));
+
+    // Undone edits are only forgotten once there's a new edit to replace them.
+    history.set_max_bytes(0);
+    assert_eq!(history.bytes(), 2 * edit_bytes);
+    history.set_block(&mut svo, &[7, 0], VoxelData::new(1));
+    assert_eq!(history.bytes(), 0);
+    assert!(!history.can_undo() && !history.can_redo());
+}
+
+#[test]
+fn undo_all_restores_original() {
+    fn check_undo_all(svo: SVO, edits: Vec<(u8, u8, bool)>) -> bool {
+        let mut edited = svo.clone();
+        let mut history = EditHistory::new(1 << 20);
+        for &(a, b, solid) in &edits {
+            history.set_block(&mut edited, &[a % 8, b % 8], VoxelData::new(solid as i32));
+        }
+        let after = edited.clone();
+        while history.undo(&mut edited) {}
+        let undone = edited.same_voxels(&svo, 3);
+        while history.redo(&mut edited) {}
+        undone && edited == after
+    }
+    quickcheck(check_undo_all as fn(SVO, Vec<(u8, u8, bool)>) -> bool)
+}
+
+#[test]
+fn oversized_edits_are_reported() {
+    let edit_bytes = 2 * size_of::<SVO>();
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    let mut history = EditHistory::new(2 * edit_bytes);
+    assert!(history.set_block(&mut svo, &[0, 0], VoxelData::new(1)));
+    assert!(history.can_undo());
+
+    // Filling a box replaces the whole tree, which is far more than the limit.
+    history.begin();
+    assert!(history.record(&mut svo, &[], |svo| svo.fill_coords(VoxelCoord::new(0, 0, 0, 2), VoxelCoord::new(1, 1, 1, 2), VoxelData::new(5))));
+    assert!(!history.commit());
+    assert!(!history.can_undo());
+    assert_eq!(history.bytes(), 0);
+    assert!(!history.undo(&mut svo));
+
+    // Edits that fit are kept again afterwards.
+    assert!(history.set_block(&mut svo, &[7, 7], VoxelData::new(2)));
+    assert!(history.can_undo());
+}
@@ -0,0 +1,49 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write, Result};
+use svo::voxel_data::VoxelData;
+use svo::material::{Material, MaterialRegistry};
+
+// Everything an SVO needs to know about the payload stored in its leaves.
+// Equality is used to decide when eight octants can be recombined into a single voxel.
+pub trait LeafData: Copy + PartialEq {
+    // Empty leaves are skipped by the raycaster and aren't rendered.
+    fn is_empty(&self) -> bool;
+
+    // The data that removing something leaves behind, which must be empty.
+    fn empty() -> Self;
+
+    // What the leaf is made of, for drawing and raycasting. Data that doesn't have a material type
+    // is either empty or unknown, and unknown materials are solid.
+    fn material<'a>(&self, registry: &'a MaterialRegistry) -> &'a Material {
+        if self.is_empty() { registry.empty() } else { registry.unknown() }
+    }
+
+    fn read_from<R: Read + ?Sized>(reader: &mut R) -> Result<Self>;
+
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()>;
+}
+
+impl LeafData for VoxelData {
+    // Type 0 is nothing at all. Other types that aren't solid, like water, still get drawn, and only
+    // cast_ray_with lets rays through them since that's the one that knows the materials.
+    fn is_empty(&self) -> bool {
+        self.voxel_type == 0
+    }
+
+    fn empty() -> VoxelData {
+        VoxelData::new(0)
+    }
+
+    fn material<'a>(&self, registry: &'a MaterialRegistry) -> &'a Material {
+        registry.get(self.voxel_type)
+    }
+
+    fn read_from<R: Read + ?Sized>(reader: &mut R) -> Result<VoxelData> {
+        let voxel_type = try! { reader.read_i32::<LittleEndian>() };
+        Ok(VoxelData::new(voxel_type))
+    }
+
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i32::<LittleEndian>(self.voxel_type)
+    }
+}
@@ -0,0 +1,159 @@
+use nalgebra::Vector3;
+use svo::*;
+use svo::cast_ray::cast_ray_octree;
+use svo::query::voxel_at_octree;
+use svo::traverse::{OctreeNode, visit_octree};
+
+#[cfg(test)]
+mod test;
+
+// How to pick one value for a subtree that's too deep for the level of detail.
+pub trait LodPolicy<T> {
+    // Each voxel in the subtree comes with the fraction of the subtree's volume that it fills.
+    fn reduce(&self, voxels: &[(T, f32)]) -> T;
+}
+
+// Any closure over the subtree's voxels is a policy.
+impl<T, F: Fn(&[(T, f32)]) -> T> LodPolicy<T> for F {
+    fn reduce(&self, voxels: &[(T, f32)]) -> T {
+        self(voxels)
+    }
+}
+
+// Whatever fills the most volume, with ties going to the first in octant index order.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Majority;
+
+impl<T: LeafData> LodPolicy<T> for Majority {
+    fn reduce(&self, voxels: &[(T, f32)]) -> T {
+        let mut totals: Vec<(T, f32)> = Vec::new();
+        for &(data, volume) in voxels {
+            match totals.iter().position(|&(total_data, _)| total_data == data) {
+                Some(i) => totals[i].1 += volume,
+                None => totals.push((data, volume)),
+            }
+        }
+        totals.iter().fold(totals[0], |best, &total| if total.1 > best.1 { total } else { best }).0
+    }
+}
+
+// Solid if anything in the subtree is, using the majority of the solid voxels.
+// Thin features like walls survive this, where they'd disappear with Majority.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct AnySolid;
+
+impl<T: LeafData> LodPolicy<T> for AnySolid {
+    fn reduce(&self, voxels: &[(T, f32)]) -> T {
+        let solid: Vec<(T, f32)> = voxels.iter().cloned().filter(|&(data, _)| !data.is_empty()).collect();
+        if solid.is_empty() { voxels[0].0 } else { Majority.reduce(&solid) }
+    }
+}
+
+impl<T: LeafData> SVO<T> {
+    // A copy of the SVO with every subtree below max_depth collapsed into one voxel.
+    pub fn lod<P: LodPolicy<T>>(&self, max_depth: u32, policy: &P) -> SVO<T> {
+        self.lod_view(max_depth, policy).to_svo()
+    }
+
+    // The SVO as it would look after lod, without copying it.
+    pub fn lod_view<'a, P: LodPolicy<T>>(&'a self, max_depth: u32, policy: &'a P) -> LodView<'a, T, P> {
+        LodView { svo: self, max_depth: max_depth, policy: policy }
+    }
+
+    fn subtree_voxels(&self, volume: f32, voxels: &mut Vec<(T, f32)>) {
+        match *self {
+            SVO::Voxel { data, .. } => voxels.push((data, volume)),
+            SVO::Octants(ref octants) => {
+                for octant in octants.iter() {
+                    octant.subtree_voxels(volume / 8., voxels);
+                }
+            }
+        }
+    }
+}
+
+pub struct LodView<'a, T: 'a, P: 'a> {
+    svo: &'a SVO<T>,
+    max_depth: u32,
+    policy: &'a P,
+}
+
+impl<'a, T: LeafData, P: LodPolicy<T>> LodView<'a, T, P> {
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    // The data of a node, which must either be a voxel or at max_depth.
+    fn reduce(&self, svo: &SVO<T>) -> T {
+        match *svo {
+            SVO::Voxel { data, .. } => data,
+            SVO::Octants(_) => {
+                let mut voxels = Vec::new();
+                svo.subtree_voxels(1., &mut voxels);
+                self.policy.reduce(&voxels)
+            }
+        }
+    }
+
+    pub fn to_svo(&self) -> SVO<T> {
+        self.to_svo_helper(self.svo, 0)
+    }
+
+    fn to_svo_helper(&self, svo: &SVO<T>, depth: u32) -> SVO<T> {
+        match *svo {
+            SVO::Octants(ref octants) if depth < self.max_depth => {
+                let mut new_svo = SVO::new_octants(|ix| self.to_svo_helper(&octants[ix as usize], depth + 1));
+                new_svo.recombine_svo();
+                new_svo
+            }
+            _ => SVO::new_voxel(self.reduce(svo)),
+        }
+    }
+
+    // See SVO::visit. Collapsed subtrees are visited as single leaves.
+    pub fn visit<V: SvoVisitor<T>>(&self, visitor: &mut V) {
+        visit_octree(self.root(), visitor);
+    }
+
+    // See SVO::voxel_at.
+    pub fn voxel_at(&self, point: Vector3<f32>) -> Option<(T, u32)> {
+        voxel_at_octree(self.root(), point)
+    }
+
+    // See SVO::cast_ray.
+    pub fn cast_ray(&self, ray_origin: Vector3<f32>, ray_dir: Vector3<f32>) -> Option<Vector3<f32>> {
+        cast_ray_octree(self.root(), ray_origin, ray_dir, &|data: &T| !data.is_empty())
+    }
+
+    fn root(&self) -> LodNode<T, P> {
+        LodNode { view: self, svo: self.svo, depth: 0 }
+    }
+}
+
+// A node of a LodView, which is a leaf once it's at max_depth.
+struct LodNode<'a, T: 'a, P: 'a> {
+    view: &'a LodView<'a, T, P>,
+    svo: &'a SVO<T>,
+    depth: u32,
+}
+
+impl<'a, T, P> Clone for LodNode<'a, T, P> {
+    fn clone(&self) -> LodNode<'a, T, P> {
+        *self
+    }
+}
+
+impl<'a, T, P> Copy for LodNode<'a, T, P> {}
+
+impl<'a, T: LeafData, P: LodPolicy<T>> OctreeNode<T> for LodNode<'a, T, P> {
+    fn leaf_data(&self) -> Option<T> {
+        match *self.svo {
+            SVO::Octants(_) if self.depth < self.view.max_depth => None,
+            _ => Some(self.view.reduce(self.svo)),
+        }
+    }
+
+    fn child(&self, ix: u8) -> LodNode<'a, T, P> {
+        LodNode { view: self.view, svo: self.svo.child(ix), depth: self.depth + 1 }
+    }
+}
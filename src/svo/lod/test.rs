@@ -0,0 +1,104 @@
+use nalgebra::{Vector3, ApproxEq};
+use quickcheck::*;
+use svo::*;
+
+fn split(types: [i32; 8]) -> SVO {
+    SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new(types[ix as usize])))
+}
+
+// Octant 0 is mostly solid, octant 1 has a single solid voxel, and octant 2 is split two levels deep.
+fn terrain() -> SVO {
+    SVO::new_octants(|ix| match ix {
+        0 => split([1, 1, 1, 2, 2, 0, 0, 0]),
+        1 => split([0, 0, 0, 0, 0, 0, 3, 0]),
+        2 => SVO::new_octants(|jx| if jx == 0 { split([4, 4, 4, 4, 0, 0, 0, 0]) } else { SVO::new_voxel(VoxelData::new(0)) }),
+        _ => SVO::new_voxel(VoxelData::new(0)),
+    })
+}
+
+#[test]
+fn majority() {
+    let lod = terrain().lod(1, &Majority);
+    let expected = SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new(if ix == 0 { 1 } else { 0 })));
+    assert_eq!(lod, expected);
+}
+
+#[test]
+fn any_solid() {
+    let lod = terrain().lod(1, &AnySolid);
+    let expected = SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new([1, 3, 4, 0, 0, 0, 0, 0][ix as usize])));
+    assert_eq!(lod, expected);
+
+    assert_eq!(terrain().lod(0, &AnySolid), SVO::new_voxel(VoxelData::new(1)));
+}
+
+#[test]
+fn majority_weights_by_volume() {
+    // Octant 2 has more type 4 voxels than empty ones, but they only fill a sixteenth of it.
+    let lod = terrain().lod(2, &Majority);
+    assert_eq!(lod.get_block(VoxelCoord::new(0, 2, 0, 2)), Some(VoxelData::new(4)));
+    assert_eq!(terrain().lod(1, &Majority).get_block(VoxelCoord::new(0, 1, 0, 1)), Some(VoxelData::new(0)));
+}
+
+#[test]
+fn custom_reducer() {
+    let highest = |voxels: &[(VoxelData, f32)]| *voxels.iter().map(|&(ref data, _)| data).max_by_key(|data| data.voxel_type).unwrap();
+    let lod = terrain().lod(1, &highest);
+    let expected = SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new([2, 3, 4, 0, 0, 0, 0, 0][ix as usize])));
+    assert_eq!(lod, expected);
+}
+
+#[test]
+fn deep_lod_keeps_everything() {
+    assert_eq!(terrain().lod(3, &Majority), terrain());
+    assert_eq!(terrain().lod(10, &AnySolid), terrain());
+}
+
+#[test]
+fn view_voxel_at() {
+    let svo = terrain();
+    let view = svo.lod_view(1, &AnySolid);
+    assert_eq!(view.voxel_at(Vector3::new(0.7, 0.2, 0.2)), Some((VoxelData::new(3), 1)));
+    assert_eq!(view.voxel_at(Vector3::new(0.7, 0.7, 0.7)), Some((VoxelData::new(0), 1)));
+    assert_eq!(view.voxel_at(Vector3::new(2.0, 0.7, 0.7)), None);
+    assert_eq!(svo.lod_view(0, &AnySolid).voxel_at(Vector3::new(0.7, 0.7, 0.7)), Some((VoxelData::new(1), 0)));
+}
+
+#[test]
+fn view_cast_ray() {
+    let svo = terrain();
+    // Misses the single solid voxel in octant 1 at full detail, but hits it once it fills the octant.
+    let (origin, dir) = (Vector3::new(0.6, 0.1, -1.0), Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(svo.cast_ray(origin, dir), None);
+    assert_approx_eq_eps!(svo.lod_view(1, &AnySolid).cast_ray(origin, dir).unwrap(), Vector3::new(0.6, 0.1, 0.0), 0.01);
+}
+
+#[test]
+fn view_matches_copy() {
+    fn check_view_matches_copy(svo: SVO, depth: u8, any_solid: bool) -> bool {
+        let depth = (depth % 4) as u32;
+        if any_solid { check_policy(&svo, depth, &AnySolid) } else { check_policy(&svo, depth, &Majority) }
+    }
+    quickcheck(check_view_matches_copy as fn(SVO, u8, bool) -> bool)
+}
+
+fn check_policy<P: LodPolicy<VoxelData>>(svo: &SVO, depth: u32, policy: &P) -> bool {
+    let copy = svo.lod(depth, policy);
+    let view = svo.lod_view(depth, policy);
+    let size = 8;
+    let voxels_match = (0..size * size * size).all(|i| {
+        let coord = VoxelCoord::new(i % size, (i / size) % size, i / (size * size), 3);
+        let centre = coord.origin() + Vector3::new(0.5, 0.5, 0.5) * coord.side_len();
+        view.voxel_at(centre).map(|(data, _)| data) == copy.voxel_at(centre).map(|(data, _)| data)
+    });
+    let rays_match = (0..size).all(|i| {
+        let offset = (i as f32 + 0.5) / size as f32;
+        let origin = Vector3::new(-1.0, offset, 1.0 - offset);
+        let dir = Vector3::new(1.0, 0.1, 0.2);
+        match (view.cast_ray(origin, dir), copy.cast_ray(origin, dir)) {
+            (Some(a), Some(b)) => a.approx_eq_eps(&b, &0.001),
+            (a, b) => a == b,
+        }
+    });
+    voxels_match && rays_match
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Result, Error, ErrorKind};
+use std::path::Path;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Material {
+    pub name: String,
+    // RGBA, from 0 to 1.
+    pub colour: [f32; 4],
+    // Solid materials stop rays and are used for collision.
+    pub solid: bool,
+    // Transparent materials are drawn with their alpha, and fully transparent ones aren't drawn at all.
+    pub transparent: bool,
+    pub emissive: bool,
+}
+
+impl Material {
+    pub fn is_visible(&self) -> bool {
+        !self.transparent || self.colour[3] > 0.
+    }
+}
+
+// What each voxel_type is made of, usually loaded from a file like src/data/materials.txt.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MaterialRegistry {
+    materials: HashMap<i32, Material>,
+    unknown: Material,
+    empty: Material,
+}
+
+impl MaterialRegistry {
+    // A registry with nothing in it, where every type is unknown.
+    pub fn new() -> MaterialRegistry {
+        MaterialRegistry {
+            materials: HashMap::new(),
+            unknown: Material {
+                name: "unknown".to_string(),
+                colour: [1., 0., 1., 1.],
+                solid: true,
+                transparent: false,
+                emissive: false,
+            },
+            empty: Material {
+                name: "empty".to_string(),
+                colour: [0., 0., 0., 0.],
+                solid: false,
+                transparent: true,
+                emissive: false,
+            },
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<MaterialRegistry> {
+        let mut text = String::new();
+        try!{ File::open(path).and_then(|mut file| file.read_to_string(&mut text)) };
+        MaterialRegistry::parse(&text)
+    }
+
+    // Blank lines and anything after a # are ignored.
+    pub fn parse(text: &str) -> Result<MaterialRegistry> {
+        let mut registry = MaterialRegistry::new();
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() { continue; }
+            let (id, material) = try!{ parse_line(line).map_err(|msg| {
+                let msg = format!("Invalid material on line {}: {}", line_number + 1, msg);
+                Error::new(ErrorKind::InvalidData, msg)
+            }) };
+            if registry.materials.insert(id, material).is_some() {
+                let msg = format!("Material {} is defined twice, the second time on line {}", id, line_number + 1);
+                return Err(Error::new(ErrorKind::InvalidData, msg));
+            }
+        }
+        Ok(registry)
+    }
+
+    // Loads the registry at path, or falls back to the built in materials if it can't be read.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> MaterialRegistry {
+        MaterialRegistry::load(&path).unwrap_or_else(|e| {
+            warn!("Couldn't load materials from {}, using the built in ones: {}", path.as_ref().display(), e);
+            MaterialRegistry::default()
+        })
+    }
+
+    pub fn insert(&mut self, id: i32, material: Material) -> Option<Material> {
+        self.materials.insert(id, material)
+    }
+
+    // Types that aren't registered are solid, like they were before there was a registry.
+    pub fn get(&self, id: i32) -> &Material {
+        self.materials.get(&id).unwrap_or(&self.unknown)
+    }
+
+    pub fn id_of(&self, name: &str) -> Option<i32> {
+        self.materials.iter().find(|&(_, material)| material.name == name).map(|(&id, _)| id)
+    }
+
+    // What leaf data without a voxel_type is made of, see LeafData::material.
+    pub fn unknown(&self) -> &Material {
+        &self.unknown
+    }
+
+    pub fn empty(&self) -> &Material {
+        &self.empty
+    }
+}
+
+impl Default for MaterialRegistry {
+    // The materials in src/data/materials.txt.
+    fn default() -> MaterialRegistry {
+        MaterialRegistry::parse(include_str!("../../data/materials.txt")).expect("The built in materials are invalid")
+    }
+}
+
+fn parse_line(line: &str) -> ::std::result::Result<(i32, Material), String> {
+    let mut words = line.split_whitespace();
+    let id = try!{ words.next().unwrap().parse::<i32>().map_err(|e| format!("bad id: {}", e)) };
+    let name = try!{ words.next().ok_or("missing name".to_string()) };
+    let colour = try!{ words.next().ok_or("missing colour".to_string()).and_then(parse_colour) };
+    let mut material = Material {
+        name: name.to_string(),
+        colour: colour,
+        solid: false,
+        transparent: false,
+        emissive: false,
+    };
+    for flag in words {
+        match flag {
+            "solid" => material.solid = true,
+            "transparent" => material.transparent = true,
+            "emissive" => material.emissive = true,
+            other => return Err(format!("unknown flag '{}'", other)),
+        }
+    }
+    Ok((id, material))
+}
+
+fn parse_colour(hex: &str) -> ::std::result::Result<[f32; 4], String> {
+    if hex.len() != 8 || !hex.chars().all(|c| c.is_digit(16)) {
+        return Err(format!("colour '{}' isn't 8 hex digits", hex));
+    }
+    let mut colour = [0.; 4];
+    for i in 0..4 {
+        colour[i] = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap() as f32 / 255.;
+    }
+    Ok(colour)
+}
@@ -0,0 +1,68 @@
+use nalgebra::{Vector3, ApproxEq};
+use svo::*;
+use svo::material::*;
+
+#[test]
+fn parse_materials() {
+    let registry = MaterialRegistry::parse("
+        # comment
+        0 air 00000000 transparent
+        7 lamp FF8000FF solid emissive # trailing comment
+    ").unwrap();
+    assert_eq!(registry.get(0).name, "air");
+    assert!(!registry.get(0).is_visible());
+    assert_eq!(*registry.get(7), Material {
+        name: "lamp".to_string(),
+        colour: [1., 128. / 255., 0., 1.],
+        solid: true,
+        transparent: false,
+        emissive: true,
+    });
+    assert_eq!(registry.id_of("lamp"), Some(7));
+    assert_eq!(registry.id_of("stone"), None);
+}
+
+#[test]
+fn unknown_types_are_solid() {
+    let registry = MaterialRegistry::new();
+    assert!(registry.get(3).solid);
+    assert!(registry.get(3).is_visible());
+}
+
+#[test]
+fn parse_errors() {
+    assert!(MaterialRegistry::parse("x air 00000000").is_err());
+    assert!(MaterialRegistry::parse("0 air").is_err());
+    assert!(MaterialRegistry::parse("0 air 000000").is_err());
+    assert!(MaterialRegistry::parse("0 air 0000000g").is_err());
+    assert!(MaterialRegistry::parse("0 air 00000000 fluffy").is_err());
+    assert!(MaterialRegistry::parse("0 air 00000000\n0 void 00000000").is_err());
+}
+
+#[test]
+fn default_materials() {
+    let registry = MaterialRegistry::default();
+    assert!(!registry.get(registry.id_of("air").unwrap()).solid);
+    assert!(registry.get(registry.id_of("stone").unwrap()).solid);
+}
+
+#[test]
+fn emptiness_doesnt_need_a_registry() {
+    let registry = MaterialRegistry::default();
+    let water = VoxelData::new(registry.id_of("water").unwrap());
+    assert!(!water.is_empty());
+    assert!(!registry.get(water.voxel_type).solid);
+    assert!(VoxelData::new(0).is_empty());
+    assert_eq!(VoxelData::empty(), VoxelData::new(0));
+}
+
+#[test]
+fn rays_pass_through_non_solid_materials() {
+    let registry = MaterialRegistry::default();
+    let water = registry.id_of("water").unwrap();
+    let stone = registry.id_of("stone").unwrap();
+    let svo = SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new(if ix == 1 { stone } else { water })));
+    let (origin, dir) = (Vector3::new(-1., 0.25, 0.25), Vector3::new(1., 0., 0.));
+    assert_approx_eq_eps!(svo.cast_ray(origin, dir).unwrap(), Vector3::new(0., 0.25, 0.25), 0.01);
+    assert_approx_eq_eps!(svo.cast_ray_with(&registry, origin, dir).unwrap(), Vector3::new(0.5, 0.25, 0.25), 0.01);
+}
@@ -0,0 +1,142 @@
+use nalgebra::Vector3;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// Ready-made ways to combine two voxels, see SVO::merge_with.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum MergeMode {
+    // Solid wherever either is, keeping the first SVO's data where both are.
+    Union,
+    // Solid only where both are, keeping the first SVO's data there and the second's empty data
+    // everywhere else.
+    Intersection,
+    // The first SVO with everything solid in the second replaced by LeafData::empty.
+    Difference,
+    // The second SVO laid over the first, so its solid voxels replace whatever was there.
+    Overlay,
+}
+
+impl MergeMode {
+    pub fn combine<T: LeafData>(&self, a: T, b: T) -> T {
+        match *self {
+            MergeMode::Union => if a.is_empty() { b } else { a },
+            MergeMode::Intersection => if b.is_empty() { b } else { a },
+            MergeMode::Difference => if b.is_empty() { a } else { T::empty() },
+            MergeMode::Overlay => if b.is_empty() { a } else { b },
+        }
+    }
+}
+
+impl<T: LeafData> SVO<T> {
+    // Combine two SVOs voxel by voxel. Both trees are walked together, but nothing is built for a
+    // subtree that comes out the same as one of the two it was merged from, so a voxel is only
+    // split where the merged data actually differs within it. The result is recombined.
+    pub fn merge<F>(&self, other: &SVO<T>, combine: F) -> SVO<T>
+            where F: Fn(T, T) -> T {
+        match self.merge_helper(other, &combine) {
+            Merged::Both | Merged::First => self.clone(),
+            Merged::Second => other.clone(),
+            Merged::New(merged) => merged,
+        }
+    }
+
+    pub fn merge_with(&self, other: &SVO<T>, mode: MergeMode) -> SVO<T> {
+        self.merge(other, |a, b| mode.combine(a, b))
+    }
+
+    // Like merge, but the result replaces this SVO and every leaf that appears or disappears is
+    // registered or deregistered. Leaves that the other SVO doesn't change keep their ids.
+    // The origin and depth are where this SVO is in the one that registration is tracking.
+    pub fn merge_into_with<F>(&mut self,
+                              registration: &RegistrationFunctions<T>,
+                              other: &SVO<T>,
+                              combine: &F,
+                              origin: Vector3<f32>,
+                              depth: i32)
+            where F: Fn(T, T) -> T {
+        match (self.get_voxel_data(), other) {
+            (Some(a), &SVO::Voxel { data: b, .. }) => {
+                let merged = combine(a, b);
+                if merged != a {
+                    self.deregister_all(registration);
+                    *self = SVO::new_registered_voxel(registration, merged, origin, depth);
+                }
+                return;
+            },
+            (Some(_), &SVO::Octants(_)) => self.subdivide_voxel_with(registration, origin, depth),
+            (None, _) => {},
+        }
+        if let SVO::Octants(ref mut octants) = *self {
+            for ix in 0..8 {
+                let child_other = match *other {
+                    SVO::Octants(ref others) => &others[ix as usize],
+                    SVO::Voxel { .. } => other,
+                };
+                octants[ix as usize].merge_into_with(registration, child_other, combine, origin + offset(ix, depth), depth + 1);
+            }
+        }
+        self.recombine_svo_with(registration, origin, depth);
+    }
+
+    fn merge_helper<F>(&self, other: &SVO<T>, combine: &F) -> Merged<T>
+            where F: Fn(T, T) -> T {
+        let children: Vec<Merged<T>> = match (self, other) {
+            (&SVO::Voxel { data: a, .. }, &SVO::Voxel { data: b, .. }) => {
+                let merged = combine(a, b);
+                return match (merged == a, merged == b) {
+                    (true, true) => Merged::Both,
+                    (true, false) => Merged::First,
+                    (false, true) => Merged::Second,
+                    (false, false) => Merged::New(SVO::new_voxel(merged)),
+                };
+            },
+            (&SVO::Voxel { .. }, &SVO::Octants(ref others)) =>
+                others.iter().map(|child_other| self.merge_helper(child_other, combine)).collect(),
+            (&SVO::Octants(ref octants), &SVO::Voxel { .. }) =>
+                octants.iter().map(|child| child.merge_helper(other, combine)).collect(),
+            (&SVO::Octants(ref octants), &SVO::Octants(ref others)) =>
+                octants.iter().zip(others.iter())
+                    .map(|(child, child_other)| child.merge_helper(child_other, combine)).collect(),
+        };
+
+        let same_as_first = children.iter().all(|child| match *child {
+            Merged::Both | Merged::First => true,
+            _ => false,
+        });
+        let same_as_second = children.iter().all(|child| match *child {
+            Merged::Both | Merged::Second => true,
+            _ => false,
+        });
+        match (same_as_first, same_as_second) {
+            (true, true) => return Merged::Both,
+            (true, false) => return Merged::First,
+            (false, true) => return Merged::Second,
+            (false, false) => {},
+        }
+
+        let mut children = children.into_iter();
+        let mut merged = SVO::new_octants(|ix| match children.next().unwrap() {
+            Merged::Both | Merged::First => match *self {
+                SVO::Octants(ref octants) => *octants[ix as usize].clone(),
+                SVO::Voxel { .. } => self.clone(),
+            },
+            Merged::Second => match *other {
+                SVO::Octants(ref others) => *others[ix as usize].clone(),
+                SVO::Voxel { .. } => other.clone(),
+            },
+            Merged::New(merged) => merged,
+        });
+        merged.recombine_svo();
+        Merged::New(merged)
+    }
+}
+
+// The result of merging two subtrees, which is left unbuilt when it's just one of them.
+enum Merged<T> {
+    Both,
+    First,
+    Second,
+    New(SVO<T>),
+}
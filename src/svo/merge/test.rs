@@ -0,0 +1,105 @@
+use quickcheck::*;
+use svo::*;
+
+fn check_merge(a: &SVO, b: &SVO, mode: MergeMode) -> bool {
+    let merged = a.merge_with(b, mode);
+    let size = 8;
+    (0..size * size * size).all(|i| {
+        let coord = VoxelCoord::new(i % size, (i / size) % size, i / (size * size), 3);
+        merged.get_block(coord) == Some(mode.combine(a.get_block(coord).unwrap(), b.get_block(coord).unwrap()))
+    })
+}
+
+// Solid in octants 0, 1 and 2, with a type 2 block inside octant 1.
+fn stone() -> SVO {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[0], VoxelData::new(1));
+    svo.set_block(&[1], VoxelData::new(1));
+    svo.set_block(&[2], VoxelData::new(1));
+    svo.set_block(&[1, 7], VoxelData::new(2));
+    svo
+}
+
+#[test]
+fn union() {
+    let merged = SVO::floor().merge_with(&stone(), MergeMode::Union);
+    let mut expected = SVO::floor();
+    expected.set_block(&[2], VoxelData::new(1));
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn intersection() {
+    let merged = SVO::floor().merge_with(&stone(), MergeMode::Intersection);
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    expected.set_block(&[0], VoxelData::new(1));
+    expected.set_block(&[1], VoxelData::new(1));
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn difference() {
+    let merged = SVO::floor().merge_with(&stone(), MergeMode::Difference);
+    let mut expected = SVO::floor();
+    expected.set_block(&[0], VoxelData::new(0));
+    expected.set_block(&[1], VoxelData::new(0));
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn overlay() {
+    let merged = SVO::floor().merge_with(&stone(), MergeMode::Overlay);
+    let mut expected = SVO::floor();
+    expected.set_block(&[2], VoxelData::new(1));
+    expected.set_block(&[1, 7], VoxelData::new(2));
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn custom_combine() {
+    let merged = SVO::floor().merge(&stone(), |a, b| VoxelData::new(a.voxel_type + b.voxel_type));
+    assert_eq!(merged.get_block(VoxelCoord::new(0, 0, 0, 1)), Some(VoxelData::new(2)));
+    assert_eq!(merged.get_block(VoxelCoord::new(3, 1, 1, 2)), Some(VoxelData::new(3)));
+    assert_eq!(merged.get_block(VoxelCoord::new(0, 1, 0, 1)), Some(VoxelData::new(1)));
+    assert_eq!(merged.get_block(VoxelCoord::new(1, 1, 1, 1)), Some(VoxelData::new(0)));
+}
+
+#[test]
+fn only_splits_where_needed() {
+    // Merging with a single voxel doesn't split anything.
+    let merged = SVO::floor().merge_with(&SVO::new_voxel(VoxelData::new(0)), MergeMode::Union);
+    assert_eq!(merged, SVO::floor());
+
+    // And merging a covering voxel recombines everything.
+    let merged = stone().merge_with(&SVO::new_voxel(VoxelData::new(5)), MergeMode::Overlay);
+    assert_eq!(merged, SVO::new_voxel(VoxelData::new(5)));
+}
+
+#[test]
+fn only_descends_where_trees_differ() {
+    // A solid voxel covers everything in a union, so the other tree being split doesn't matter.
+    let merged = SVO::new_voxel(VoxelData::new(3)).merge_with(&stone(), MergeMode::Union);
+    assert_eq!(merged, SVO::new_voxel(VoxelData::new(3)));
+
+    // Only the octant that the other tree changes is rebuilt.
+    let mut other = SVO::new_voxel(VoxelData::new(0));
+    other.set_block(&[3, 4], VoxelData::new(7));
+    let merged = stone().merge_with(&other, MergeMode::Overlay);
+    let mut expected = stone();
+    expected.set_block(&[3, 4], VoxelData::new(7));
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn merge_matches_voxels() {
+    fn check_merge_matches_voxels(a: SVO, b: SVO, mode: u8) -> bool {
+        let mode = match mode % 4 {
+            0 => MergeMode::Union,
+            1 => MergeMode::Intersection,
+            2 => MergeMode::Difference,
+            _ => MergeMode::Overlay,
+        };
+        check_merge(&a, &b, mode)
+    }
+    quickcheck(check_merge_matches_voxels as fn(SVO, SVO, u8) -> bool)
+}
@@ -1,10 +1,32 @@
 pub mod registration;
 pub mod voxel_data;
+pub mod leaf_data;
+pub mod material;
+pub mod arena;
+pub mod cast_ray;
 
 mod set_block;
-mod cast_ray;
 mod save_load;
 mod generator;
+mod coord;
+mod query;
+mod traverse;
+mod fill_box;
+mod brush;
+mod merge;
+mod lod;
+mod stats;
+mod neighbour;
+mod components;
+mod collision;
+mod transform;
+mod stamp;
+mod history;
+mod changes;
+mod persistent;
+mod parallel;
+mod distance;
+mod dag;
 
 #[cfg(test)]
 mod test;
@@ -12,19 +34,64 @@ mod test;
 use nalgebra::Vector3;
 pub use self::registration::*;
 pub use self::voxel_data::VoxelData;
+pub use self::leaf_data::LeafData;
+pub use self::material::{Material, MaterialRegistry};
+pub use self::arena::ArenaSVO;
+pub use self::dag::DagSVO;
+pub use self::coord::{VoxelCoord, index_position};
+pub use self::traverse::{SvoVisitor, Position, Leaf};
+pub use self::brush::{Brush, BrushOp, Sphere, Cylinder, Cuboid};
+pub use self::merge::MergeMode;
+pub use self::lod::{LodPolicy, LodView, Majority, AnySolid};
+pub use self::stats::SvoStats;
+pub use self::neighbour::{Face, FACES, Neighbour};
+pub use self::components::{Connectivity, Component};
+pub use self::collision::Contact;
+pub use self::transform::AxisTransform;
+pub use self::stamp::StampMode;
+pub use self::history::EditHistory;
+pub use self::changes::{Change, SubscriptionId, TrackedSVO};
+pub use self::persistent::PersistentSVO;
+pub use self::parallel::map_octants;
+pub use self::distance::DistanceField;
 use std::io::Result;
 
 use arrayvec::ArrayVec;
-pub type SubOctants = ArrayVec<[Box<SVO>; 8]>;
+pub type SubOctants<T = VoxelData> = ArrayVec<[Box<SVO<T>>; 8]>;
 
 // Each SVO assumes that it's the cube between (0,0,0) and (1,1,1)
-#[derive(Debug, PartialEq)]
-pub enum SVO {
-    Voxel { data: VoxelData },
+// The leaves can hold any LeafData, but unless told otherwise they hold a VoxelData.
+#[derive(Debug)]
+pub enum SVO<T = VoxelData> {
+    // external_id is whatever RegistrationFunctions::register returned for this leaf, or 0 if it
+    // was made without registering it.
+    Voxel { data: T, external_id: u32 },
 
     // For a given point (x, y, z), the index of its octant is
-    // ((x >= 0.5) << 0) | ((y >= 0.5) << 1) | ((z <= 0.5) << 2)
-    Octants (SubOctants),
+    // ((x >= 0.5) << 0) | ((y >= 0.5) << 1) | ((z >= 0.5) << 2)
+    Octants (SubOctants<T>),
+}
+
+// A copy's leaves aren't registered, so they get the id 0 rather than sharing the original's ids.
+impl<T: LeafData> Clone for SVO<T> {
+    fn clone(&self) -> SVO<T> {
+        match *self {
+            SVO::Voxel { data, .. } => SVO::new_voxel(data),
+            SVO::Octants(ref octants) => SVO::new_octants(|ix| *octants[ix as usize].clone())
+        }
+    }
+}
+
+// Two SVOs are equal if they have the same structure and data. The external ids are left out, so
+// a copy is equal to the original.
+impl<T: PartialEq> PartialEq for SVO<T> {
+    fn eq(&self, other: &SVO<T>) -> bool {
+        match (self, other) {
+            (&SVO::Voxel { data: ref a, .. }, &SVO::Voxel { data: ref b, .. }) => a == b,
+            (&SVO::Octants(ref a), &SVO::Octants(ref b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 impl SVO {
@@ -34,28 +101,30 @@ impl SVO {
             SVO::new_voxel(VoxelData::new(data))
         })
     }
+}
 
-    pub fn new_voxel(voxel_data: VoxelData) -> SVO {
-        SVO::Voxel { data: voxel_data }
+impl<T: LeafData> SVO<T> {
+    pub fn new_voxel(voxel_data: T) -> SVO<T> {
+        SVO::Voxel { data: voxel_data, external_id: 0 }
     }
 
-    pub fn new_octants<F>(mut make_octant: F) -> SVO
-            where F: FnMut(u8) -> SVO {
+    pub fn new_octants<F>(mut make_octant: F) -> SVO<T>
+            where F: FnMut(u8) -> SVO<T> {
         SVO::Octants(
             (0..8).map(|i| Box::new(make_octant(i)))
-                  .collect::<SubOctants>()
+                  .collect::<SubOctants<T>>()
         )
     }
 
-    pub fn new_octants_mut_err<F>(mut make_octant: F) -> Result<SVO> 
-            where F: FnMut(u8) -> Result<SVO> {
+    pub fn new_octants_mut_err<F>(mut make_octant: F) -> Result<SVO<T>>
+            where F: FnMut(u8) -> Result<SVO<T>> {
         (0..8).map(|i| make_octant(i).map(Box::new))
-              .collect::<Result<SubOctants>>()
+              .collect::<Result<SubOctants<T>>>()
               .map(SVO::Octants)
     }
 
     // If the SVO is a Voxel, return its contents.
-    pub fn get_voxel_data(&self) -> Option<VoxelData> {
+    pub fn get_voxel_data(&self) -> Option<T> {
         match *self {
             SVO::Voxel { data, .. } => Some(data),
             _ => None
@@ -63,7 +132,7 @@ impl SVO {
     }
 
     // If the SVO is Octants, return its contents.
-    fn get_octants(&self) -> Option<&SubOctants> {
+    fn get_octants(&self) -> Option<&SubOctants<T>> {
         match *self {
             SVO::Octants(ref octants) => Some(octants),
             _ => None
@@ -80,7 +149,7 @@ pub fn above_axis(ix: u8) -> Vector3<f32> {
 
 // Returns the new origin of the child at the given index in global space.
 pub fn offset(ix: u8, depth: i32) -> Vector3<f32> {
-    let side_len = 1.0 / (1 << (depth+1)) as f32;
+    let side_len = 1.0 / (1u64 << (depth+1)) as f32;
     offset_float(ix, side_len)
 }
 
@@ -88,9 +157,11 @@ pub fn offset_float(ix: u8, side_len: f32) -> Vector3<f32> {
     above_axis(ix) * side_len
 }
 
+// The index of the octant containing the point, see the comment on SVO.
+// A point on the boundary between two octants belongs to the upper one.
 pub fn index(v: Vector3<f32>) -> u8 {
-    let above_x = (v.x > 0.5) as u8;
-    let above_y = (v.y > 0.5) as u8;
-    let above_z = (v.z > 0.5) as u8;
+    let above_x = (v.x >= 0.5) as u8;
+    let above_y = (v.y >= 0.5) as u8;
+    let above_z = (v.z >= 0.5) as u8;
     (above_x << 0) | (above_y << 1) | (above_z << 2)
 }
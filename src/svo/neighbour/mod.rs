@@ -0,0 +1,148 @@
+use svo::*;
+use svo::coord::MAX_DEPTH;
+use svo::traverse::Leaf;
+
+#[cfg(test)]
+mod test;
+
+// A side of a voxel, named by the axis it faces along.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Face {
+    NegX,
+    PosX,
+    NegY,
+    PosY,
+    NegZ,
+    PosZ,
+}
+
+pub const FACES: [Face; 6] = [Face::NegX, Face::PosX, Face::NegY, Face::PosY, Face::NegZ, Face::PosZ];
+
+impl Face {
+    // x = 0, y = 1, z = 2, matching the octant index bits.
+    pub fn axis(&self) -> u8 {
+        match *self {
+            Face::NegX | Face::PosX => 0,
+            Face::NegY | Face::PosY => 1,
+            Face::NegZ | Face::PosZ => 2,
+        }
+    }
+
+    pub fn is_positive(&self) -> bool {
+        match *self {
+            Face::PosX | Face::PosY | Face::PosZ => true,
+            Face::NegX | Face::NegY | Face::NegZ => false,
+        }
+    }
+
+    pub fn opposite(&self) -> Face {
+        match *self {
+            Face::NegX => Face::PosX,
+            Face::PosX => Face::NegX,
+            Face::NegY => Face::PosY,
+            Face::PosY => Face::NegY,
+            Face::NegZ => Face::PosZ,
+            Face::PosZ => Face::NegZ,
+        }
+    }
+
+    // The coordinate one voxel across the face, or None if that's outside of the grid.
+    pub fn step(&self, coord: VoxelCoord) -> Option<VoxelCoord> {
+        let mut next = [coord.x, coord.y, coord.z];
+        let axis = self.axis() as usize;
+        if self.is_positive() {
+            next[axis] = get!(next[axis].checked_add(1));
+        } else {
+            next[axis] = get!(next[axis].checked_sub(1));
+        }
+        let next = VoxelCoord::new(next[0], next[1], next[2], coord.depth);
+        guard!(next.in_bounds());
+        Some(next)
+    }
+}
+
+// What's on the other side of a node's face.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Neighbour<'a, T: 'a> {
+    // The face is on the boundary of the SVO's unit cube.
+    Outside,
+    // A single leaf bigger than the node covers the whole face.
+    Larger(Leaf<'a, T>),
+    // A leaf the same size as the node.
+    Same(Leaf<'a, T>),
+    // The neighbour is split, so these are the smaller leaves touching the face, in index order.
+    Subdivided(Vec<Leaf<'a, T>>),
+}
+
+impl<T: LeafData> SVO<T> {
+    // The neighbour across a face of the node at the index path. The node itself doesn't need to be a leaf,
+    // but a path that goes deeper than the leaf containing it is treated as the path to that leaf,
+    // so the leaf is never its own neighbour. Panics if the path is deeper than coord::MAX_DEPTH.
+    pub fn neighbour(&self, index: &[u8], face: Face) -> Neighbour<T> {
+        let index = &index[..self.node_depth(index)];
+        let coord = VoxelCoord::from_index(index).unwrap_or_else(|| {
+            panic!("neighbour index path is {} deep, more than the maximum of {}", index.len(), MAX_DEPTH)
+        });
+        let next = match face.step(coord) {
+            Some(next) => next,
+            None => return Neighbour::Outside,
+        };
+        let mut svo = self;
+        let mut path = Vec::new();
+        for ix in next.to_index() {
+            match *svo {
+                SVO::Voxel { ref data, .. } => return Neighbour::Larger(new_leaf(path, data)),
+                SVO::Octants(ref octants) => {
+                    path.push(ix);
+                    svo = &octants[ix as usize];
+                }
+            }
+        }
+        match *svo {
+            SVO::Voxel { ref data, .. } => Neighbour::Same(new_leaf(path, data)),
+            SVO::Octants(_) => {
+                let mut leaves = Vec::new();
+                svo.leaves_on_face(face.opposite(), &mut path, &mut leaves);
+                Neighbour::Subdivided(leaves)
+            }
+        }
+    }
+
+    // How far the index path goes before it reaches a leaf, or its whole length.
+    fn node_depth(&self, index: &[u8]) -> usize {
+        let mut svo = self;
+        for (depth, &ix) in index.iter().enumerate() {
+            match *svo {
+                SVO::Voxel { .. } => return depth,
+                SVO::Octants(ref octants) => svo = &octants[ix as usize],
+            }
+        }
+        index.len()
+    }
+
+    // Every leaf of this node that touches one of its faces.
+    fn leaves_on_face<'a>(&'a self, face: Face, path: &mut Vec<u8>, leaves: &mut Vec<Leaf<'a, T>>) {
+        match *self {
+            SVO::Voxel { ref data, .. } => leaves.push(new_leaf(path.clone(), data)),
+            SVO::Octants(ref octants) => {
+                let side = if face.is_positive() { 1 } else { 0 };
+                for ix in (0..8).filter(|ix| (ix >> face.axis()) & 1 == side) {
+                    path.push(ix);
+                    octants[ix as usize].leaves_on_face(face, path, leaves);
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+fn new_leaf<T>(index: Vec<u8>, data: &T) -> Leaf<T> {
+    let (origin, side_len) = index_position(&index);
+    Leaf {
+        origin: origin,
+        side_len: side_len,
+        depth: index.len() as u32,
+        index: index,
+        data: data,
+    }
+}
@@ -0,0 +1,90 @@
+use quickcheck::*;
+use svo::*;
+
+fn indices(neighbour: &Neighbour<VoxelData>) -> Vec<Vec<u8>> {
+    match *neighbour {
+        Neighbour::Outside => vec![],
+        Neighbour::Larger(ref leaf) | Neighbour::Same(ref leaf) => vec![leaf.index.clone()],
+        Neighbour::Subdivided(ref leaves) => leaves.iter().map(|leaf| leaf.index.clone()).collect(),
+    }
+}
+
+// Octant 1 is split, and its octant 0 is split again.
+fn svo() -> SVO {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[1, 1], VoxelData::new(1));
+    svo.set_block(&[1, 0, 3], VoxelData::new(2));
+    svo
+}
+
+#[test]
+fn outside() {
+    let svo = svo();
+    assert_eq!(svo.neighbour(&[], Face::PosX), Neighbour::Outside);
+    assert_eq!(svo.neighbour(&[1], Face::PosX), Neighbour::Outside);
+    assert_eq!(svo.neighbour(&[1, 0, 1], Face::NegY), Neighbour::Outside);
+    assert!(svo.neighbour(&[1, 0, 1], Face::NegX) != Neighbour::Outside);
+}
+
+#[test]
+fn same_size() {
+    let svo = svo();
+    match svo.neighbour(&[1, 3], Face::NegX) {
+        Neighbour::Same(leaf) => {
+            assert_eq!(leaf.index, vec![1, 2]);
+            assert_eq!(leaf.side_len, 0.25);
+        }
+        other => panic!("Expected a same size neighbour, found {:?}", other),
+    }
+    assert_eq!(indices(&svo.neighbour(&[0], Face::PosY)), vec![vec![2]]);
+}
+
+#[test]
+fn larger() {
+    let svo = svo();
+    // Across the middle of the cube from octant 1's lower x half is octant 0.
+    match svo.neighbour(&[1, 0, 2], Face::NegX) {
+        Neighbour::Larger(leaf) => {
+            assert_eq!(leaf.index, vec![0]);
+            assert_eq!(leaf.depth, 1);
+            assert_eq!(*leaf.data, VoxelData::new(0));
+        }
+        other => panic!("Expected a larger neighbour, found {:?}", other),
+    }
+    assert_eq!(indices(&svo.neighbour(&[1, 0, 6], Face::PosZ)), vec![vec![1, 4]]);
+    assert_eq!(indices(&svo.neighbour(&[1, 0, 2], Face::PosZ)), vec![vec![1, 0, 6]]);
+}
+
+#[test]
+fn subdivided() {
+    let svo = svo();
+    // The leaves of octant 1 on its lower x side, including the ones inside [1, 0].
+    let neighbours = svo.neighbour(&[0], Face::PosX);
+    assert_eq!(indices(&neighbours), vec![vec![1, 0, 0], vec![1, 0, 2], vec![1, 0, 4], vec![1, 0, 6],
+                                          vec![1, 2], vec![1, 4], vec![1, 6]]);
+    if let Neighbour::Subdivided(ref leaves) = neighbours {
+        assert!(leaves.iter().all(|leaf| leaf.origin.x == 0.5));
+    }
+}
+
+#[test]
+fn neighbours_are_symmetric() {
+    // Every leaf is reported as touching the face of its neighbours that it touches.
+    fn check_neighbours_are_symmetric(svo: SVO) -> bool {
+        svo.leaves().all(|leaf| FACES.iter().all(|&face| {
+            indices(&svo.neighbour(&leaf.index, face)).iter().all(|other| {
+                indices(&svo.neighbour(other, face.opposite())).iter().any(|back| leaf.index.starts_with(back) || back.starts_with(&leaf.index))
+            })
+        }))
+    }
+    quickcheck(check_neighbours_are_symmetric as fn(SVO) -> bool)
+}
+
+#[test]
+fn path_inside_a_leaf() {
+    let svo = svo();
+    // [0] is a leaf, so [0, 1] is treated as [0] rather than finding [0] on its lower x side.
+    assert_eq!(svo.neighbour(&[0, 1], Face::NegX), Neighbour::Outside);
+    assert_eq!(svo.neighbour(&[0, 1, 5], Face::PosY), svo.neighbour(&[0], Face::PosY));
+    assert_eq!(indices(&svo.neighbour(&[1, 1, 7], Face::NegX)), vec![vec![1, 0, 1], vec![1, 0, 3], vec![1, 0, 5], vec![1, 0, 7]]);
+}
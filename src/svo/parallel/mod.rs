@@ -0,0 +1,82 @@
+use crossbeam;
+use nalgebra::Vector3;
+use std::cmp::min;
+use svo::*;
+use svo::traverse::Leaves;
+
+#[cfg(test)]
+mod test;
+
+// Call make_octant for each of the eight octants using up to the given number of threads, and
+// tell each call how many threads it can use itself. The octants are split into contiguous runs
+// with one thread each, and the results come back in octant order whatever the thread count, so
+// anything built from them is the same as building it serially.
+pub fn map_octants<O, F>(threads: usize, make_octant: F) -> Vec<O>
+        where O: Send, F: Fn(u8, usize) -> O + Sync {
+    if threads <= 1 {
+        return (0..8).map(|ix| make_octant(ix, 1)).collect();
+    }
+
+    let runs = min(threads, 8);
+    let make_octant = &make_octant;
+    crossbeam::scope(|scope| {
+        let handles: Vec<_> = (0..runs).map(|run| {
+            let (start, end) = (run * 8 / runs, (run + 1) * 8 / runs);
+            // Threads beyond one per octant are shared out between the octants' own calls.
+            let octant_threads = threads / runs + (run < threads % runs) as usize;
+            let octant_threads = if end - start == 1 { octant_threads } else { 1 };
+            scope.spawn(move || {
+                (start..end).map(|ix| make_octant(ix as u8, octant_threads)).collect::<Vec<_>>()
+            })
+        }).collect();
+        handles.into_iter().flat_map(|handle| handle.join()).collect()
+    })
+}
+
+impl<T: LeafData + Send> SVO<T> {
+    // An SVO with the octants from map_octants, recombined like set_block would.
+    pub fn from_parallel_octants<F>(threads: usize, make_octant: F) -> SVO<T>
+            where F: Fn(u8, usize) -> SVO<T> + Sync {
+        let mut octants = map_octants(threads, make_octant).into_iter();
+        let mut svo = SVO::new_octants(|_| octants.next().unwrap());
+        svo.recombine_svo();
+        svo
+    }
+}
+
+impl<T: LeafData + Send + Sync> SVO<T> {
+    // Like leaves().filter_map(f).collect(), but subtrees are walked on up to the given number of
+    // threads. The results are in the same order as leaves().
+    pub fn filter_map_leaves<R, F>(&self, threads: usize, f: F) -> Vec<R>
+            where R: Send, F: Fn(Leaf<T>) -> Option<R> + Sync {
+        self.filter_map_leaves_helper(threads, &f, None, Vector3::new(0., 0., 0.), 1., Vec::new())
+    }
+
+    // Every voxel that overlaps the box, in the same order as leaves_in_box.
+    pub fn leaves_in_box_parallel(&self, threads: usize, min: Vector3<f32>, max: Vector3<f32>) -> Vec<(Vector3<f32>, f32, T)> {
+        let f = |leaf: Leaf<T>| Some((leaf.origin, leaf.side_len, *leaf.data));
+        self.filter_map_leaves_helper(threads, &f, Some((min, max)), Vector3::new(0., 0., 0.), 1., Vec::new())
+    }
+
+    fn filter_map_leaves_helper<R, F>(&self,
+                                      threads: usize,
+                                      f: &F,
+                                      bounds: Option<(Vector3<f32>, Vector3<f32>)>,
+                                      origin: Vector3<f32>,
+                                      side_len: f32,
+                                      index: Vec<u8>) -> Vec<R>
+            where R: Send, F: Fn(Leaf<T>) -> Option<R> + Sync {
+        match *self {
+            SVO::Octants(ref octants) if threads > 1 => {
+                let child_len = side_len / 2.;
+                map_octants(threads, |ix, octant_threads| {
+                    let mut child_index = index.clone();
+                    child_index.push(ix);
+                    octants[ix as usize].filter_map_leaves_helper(
+                        octant_threads, f, bounds, origin + offset_float(ix, child_len), child_len, child_index)
+                }).into_iter().flat_map(|results| results).collect()
+            }
+            _ => Leaves::starting_at(self, origin, side_len, index, bounds).filter_map(f).collect(),
+        }
+    }
+}
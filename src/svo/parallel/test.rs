@@ -0,0 +1,64 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use std::sync::Mutex;
+use std::thread;
+use svo::*;
+
+// A bumpy image that doesn't recombine into a few big voxels.
+fn image(size: u32) -> Vec<u8> {
+    (0..size * size).map(|i| ((i * 37 + i / size * 11) % 256) as u8).collect()
+}
+
+#[test]
+fn octants_come_back_in_order() {
+    for threads in 0..20 {
+        assert_eq!(map_octants(threads, |ix, _| ix), vec![0, 1, 2, 3, 4, 5, 6, 7], "{} threads", threads);
+    }
+}
+
+#[test]
+fn threads_are_shared_out() {
+    // With fewer threads than octants every octant runs alone, and spare threads go to the first octants.
+    assert_eq!(map_octants(3, |_, threads| threads), vec![1; 8]);
+    assert_eq!(map_octants(8, |_, threads| threads), vec![1; 8]);
+    assert_eq!(map_octants(20, |_, threads| threads), vec![3, 3, 3, 3, 2, 2, 2, 2]);
+}
+
+#[test]
+fn octants_run_on_other_threads() {
+    let ids = Mutex::new(Vec::new());
+    map_octants(8, |_, _| ids.lock().unwrap().push(thread::current().id()));
+    let mut ids = ids.into_inner().unwrap();
+    ids.sort_by_key(|id| format!("{:?}", id));
+    ids.dedup();
+    assert_eq!(ids.len(), 8);
+}
+
+#[test]
+fn parallel_height_map_matches_serial() {
+    let image = image(64);
+    let serial = SVO::height_map(5, &image, 64, 64, &MaterialRegistry::default());
+    for threads in 0..80 {
+        assert_eq!(SVO::height_map_parallel(5, &image, 64, 64, &MaterialRegistry::default(), threads), serial, "{} threads", threads);
+    }
+}
+
+#[test]
+fn parallel_leaves_match_serial() {
+    fn check_leaves(svo: SVO, threads: u8) -> bool {
+        let serial: Vec<_> = svo.leaves().map(|leaf| (leaf.index, leaf.origin, leaf.side_len)).collect();
+        let parallel = svo.filter_map_leaves(threads as usize % 20, |leaf| Some((leaf.index, leaf.origin, leaf.side_len)));
+        parallel == serial
+    }
+    quickcheck(check_leaves as fn(SVO, u8) -> bool);
+}
+
+#[test]
+fn parallel_leaves_in_box_match_serial() {
+    let svo = SVO::height_map(4, &image(32), 32, 32, &MaterialRegistry::default());
+    let (min, max) = (Vector3::new(0.2, 0.1, 0.3), Vector3::new(0.7, 0.5, 0.9));
+    let serial: Vec<_> = svo.leaves_in_box(min, max).collect();
+    for threads in 1..12 {
+        assert_eq!(svo.leaves_in_box_parallel(threads, min, max), serial);
+    }
+}
@@ -0,0 +1,163 @@
+use nalgebra::Vector3;
+use std::sync::Arc;
+use svo::*;
+use svo::cast_ray::cast_ray_octree;
+use svo::query::voxel_at_octree;
+use svo::traverse::{OctreeNode, visit_octree};
+
+#[cfg(test)]
+mod test;
+
+// An immutable SVO whose subtrees are shared between copies. Cloning one is O(1), and editing
+// returns a new root that only copies the nodes along the edited path, so old copies work as
+// snapshots that can be handed to other threads while editing carries on.
+#[derive(Debug, PartialEq)]
+pub struct PersistentSVO<T = VoxelData> {
+    root: Arc<Node<T>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Node<T> {
+    Voxel(T),
+    Octants([Arc<Node<T>>; 8]),
+}
+
+impl<T> Clone for PersistentSVO<T> {
+    fn clone(&self) -> PersistentSVO<T> {
+        PersistentSVO { root: self.root.clone() }
+    }
+}
+
+impl<T: LeafData> PersistentSVO<T> {
+    pub fn new_voxel(data: T) -> PersistentSVO<T> {
+        PersistentSVO { root: Arc::new(Node::Voxel(data)) }
+    }
+
+    pub fn from_svo(svo: &SVO<T>) -> PersistentSVO<T> {
+        PersistentSVO { root: Node::from_svo(svo) }
+    }
+
+    pub fn to_svo(&self) -> SVO<T> {
+        self.root.to_svo()
+    }
+
+    // The data of the voxel containing the index path, which may be bigger than the path's voxel,
+    // or None if the path ends at a subdivided node.
+    pub fn get(&self, index: &[u8]) -> Option<T> {
+        let mut node = &self.root;
+        for &ix in index {
+            match **node {
+                Node::Voxel(data) => return Some(data),
+                Node::Octants(ref octants) => node = &octants[ix as usize],
+            }
+        }
+        match **node {
+            Node::Voxel(data) => Some(data),
+            Node::Octants(_) => None,
+        }
+    }
+
+    // See SVO::voxel_at.
+    pub fn voxel_at(&self, point: Vector3<f32>) -> Option<(T, u32)> {
+        voxel_at_octree(&*self.root, point)
+    }
+
+    // See SVO::cast_ray.
+    pub fn cast_ray(&self, ray_origin: Vector3<f32>, ray_dir: Vector3<f32>) -> Option<Vector3<f32>> {
+        cast_ray_octree(&*self.root, ray_origin, ray_dir, &|data: &T| !data.is_empty())
+    }
+
+    // See SVO::visit.
+    pub fn visit<V: SvoVisitor<T>>(&self, visitor: &mut V) {
+        visit_octree(&*self.root, visitor);
+    }
+
+    // A copy with the block at the index path set, recombining like SVO::set_block.
+    // Everything off the edited path is shared with this SVO.
+    pub fn set_block(&self, index: &[u8], new_data: T) -> PersistentSVO<T> {
+        PersistentSVO { root: set_block(&self.root, index, new_data) }
+    }
+
+    // Whether the two SVOs are the same snapshot, without comparing their contents.
+    pub fn same_root(&self, other: &PersistentSVO<T>) -> bool {
+        same_node(&self.root, &other.root)
+    }
+}
+
+impl<T: LeafData> Node<T> {
+    fn from_svo(svo: &SVO<T>) -> Arc<Node<T>> {
+        Arc::new(match *svo {
+            SVO::Voxel { data, .. } => Node::Voxel(data),
+            SVO::Octants(ref octants) => Node::Octants(new_octants(|ix| Node::from_svo(&octants[ix as usize]))),
+        })
+    }
+
+    fn to_svo(&self) -> SVO<T> {
+        match *self {
+            Node::Voxel(data) => SVO::new_voxel(data),
+            Node::Octants(ref octants) => SVO::new_octants(|ix| octants[ix as usize].to_svo()),
+        }
+    }
+
+    fn voxel_data(&self) -> Option<T> {
+        match *self {
+            Node::Voxel(data) => Some(data),
+            Node::Octants(_) => None,
+        }
+    }
+}
+
+impl<'a, T: LeafData> OctreeNode<T> for &'a Node<T> {
+    fn leaf_data(&self) -> Option<T> {
+        self.voxel_data()
+    }
+
+    fn child(&self, ix: u8) -> &'a Node<T> {
+        let node: &'a Node<T> = *self;
+        match *node {
+            Node::Octants(ref octants) => &octants[ix as usize],
+            Node::Voxel(_) => panic!("A voxel has no octants"),
+        }
+    }
+}
+
+fn same_node<T>(a: &Arc<Node<T>>, b: &Arc<Node<T>>) -> bool {
+    &**a as *const Node<T> == &**b as *const Node<T>
+}
+
+fn new_octants<T, F>(mut make_octant: F) -> [Arc<Node<T>>; 8]
+        where F: FnMut(u8) -> Arc<Node<T>> {
+    [make_octant(0), make_octant(1), make_octant(2), make_octant(3),
+     make_octant(4), make_octant(5), make_octant(6), make_octant(7)]
+}
+
+fn set_block<T: LeafData>(node: &Arc<Node<T>>, index: &[u8], new_data: T) -> Arc<Node<T>> {
+    if node.voxel_data() == Some(new_data) { return node.clone(); } // nothing to do
+
+    let (&ix, rest) = match index.split_first() {
+        Some(split) => split,
+        None => return Arc::new(Node::Voxel(new_data)),
+    };
+
+    // Voxels get split up into shared copies of themselves, and only the edited octant is new.
+    let octants = match **node {
+        Node::Voxel(data) => {
+            let voxel = Arc::new(Node::Voxel(data));
+            new_octants(|i| if i == ix { set_block(&voxel, rest, new_data) } else { voxel.clone() })
+        }
+        Node::Octants(ref octants) => {
+            let octant = &octants[ix as usize];
+            let edited = set_block(octant, rest, new_data);
+            // Nothing changed further down, so this node can be shared too.
+            if same_node(&edited, octant) { return node.clone(); }
+            let mut edited = Some(edited);
+            new_octants(|i| if i == ix { edited.take().unwrap() } else { octants[i as usize].clone() })
+        }
+    };
+
+    // Recombine if every octant is now the same voxel.
+    match octants[0].voxel_data() {
+        Some(data) if octants[1..].iter().all(|octant| octant.voxel_data() == Some(data)) => Arc::new(Node::Voxel(data)),
+        _ => Arc::new(Node::Octants(octants)),
+    }
+}
@@ -0,0 +1,102 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use std::sync::Arc;
+use std::thread;
+use svo::*;
+use super::Node;
+
+#[test]
+fn round_trips_svo() {
+    let svo = SVO::example();
+    assert_eq!(PersistentSVO::from_svo(&svo).to_svo(), svo);
+}
+
+#[test]
+fn snapshots_are_unchanged_by_edits() {
+    let snapshot = PersistentSVO::from_svo(&SVO::floor());
+    let edited = snapshot.set_block(&[2, 1], VoxelData::new(1));
+    assert_eq!(snapshot.to_svo(), SVO::floor());
+    assert_eq!(edited.get(&[2, 1]), Some(VoxelData::new(1)));
+    assert_eq!(edited.get(&[2, 0]), Some(VoxelData::new(0)));
+    assert!(!edited.same_root(&snapshot));
+    assert!(snapshot.clone().same_root(&snapshot));
+}
+
+#[test]
+fn get_subdivided_node() {
+    let snapshot = PersistentSVO::from_svo(&SVO::floor());
+    assert_eq!(snapshot.get(&[]), None);
+    assert_eq!(snapshot.get(&[0]), Some(VoxelData::new(1)));
+    assert_eq!(snapshot.get(&[0, 7, 7]), Some(VoxelData::new(1)));
+}
+
+#[test]
+fn unedited_subtrees_are_shared() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[6, 3], VoxelData::new(2));
+    let snapshot = PersistentSVO::from_svo(&svo);
+    let edited = snapshot.set_block(&[2, 1], VoxelData::new(1));
+    match (&*snapshot.root, &*edited.root) {
+        (&Node::Octants(ref old), &Node::Octants(ref new)) => for ix in 0..8 {
+            let shared = &*old[ix] as *const Node<_> == &*new[ix] as *const Node<_>;
+            assert_eq!(shared, ix != 2, "octant {}", ix);
+        },
+        _ => panic!("Expected both roots to be subdivided"),
+    }
+}
+
+#[test]
+fn no_op_edit_keeps_root() {
+    let snapshot = PersistentSVO::from_svo(&SVO::floor());
+    assert!(snapshot.set_block(&[0, 5], VoxelData::new(1)).same_root(&snapshot));
+}
+
+#[test]
+fn snapshot_can_be_sent_to_another_thread() {
+    let snapshot = PersistentSVO::from_svo(&SVO::example());
+    let edited = snapshot.set_block(&[3], VoxelData::new(1));
+    let shared = Arc::new(snapshot.clone());
+    let rendered = thread::spawn(move || shared.to_svo()).join().unwrap();
+    assert_eq!(rendered, SVO::example());
+    assert_eq!(edited.get(&[3]), Some(VoxelData::new(1)));
+}
+
+#[test]
+fn same_as_svo_set_block() {
+    fn check_edits(edits: Vec<(u8, u8, bool)>) -> bool {
+        let mut svo = SVO::floor();
+        let mut persistent = PersistentSVO::from_svo(&svo);
+        for (a, b, solid) in edits {
+            let index = [a % 8, b % 8];
+            let data = VoxelData::new(solid as i32);
+            svo.set_block(&index, data);
+            persistent = persistent.set_block(&index, data);
+        }
+        persistent.to_svo() == svo
+    }
+    quickcheck(check_edits as fn(Vec<(u8, u8, bool)>) -> bool)
+}
+
+struct LeafList(Vec<(Vector3<f32>, f32, VoxelData)>);
+
+impl SvoVisitor<VoxelData> for LeafList {
+    fn visit_leaf(&mut self, position: &Position, data: &VoxelData) {
+        self.0.push((position.origin, position.side_len, *data));
+    }
+}
+
+#[test]
+fn same_queries_as_svo() {
+    fn check_queries(svo: SVO, x: u8, y: u8, z: u8) -> bool {
+        let persistent = PersistentSVO::from_svo(&svo);
+        let point = Vector3::new(x as f32, y as f32, z as f32) / 255.;
+        let ray_origin = Vector3::new(-1., 0.5, 0.5);
+        let ray_dir = point - ray_origin;
+        let mut leaves = LeafList(Vec::new());
+        persistent.visit(&mut leaves);
+        persistent.voxel_at(point) == svo.voxel_at(point) &&
+            persistent.cast_ray(ray_origin, ray_dir) == svo.cast_ray(ray_origin, ray_dir) &&
+            leaves.0 == svo.leaves().map(|leaf| (leaf.origin, leaf.side_len, *leaf.data)).collect::<Vec<_>>()
+    }
+    quickcheck(check_queries as fn(SVO, u8, u8, u8) -> bool)
+}
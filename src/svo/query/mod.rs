@@ -0,0 +1,69 @@
+use nalgebra::Vector3;
+use svo::*;
+use svo::traverse::{Leaves, OctreeNode};
+
+#[cfg(test)]
+mod test;
+
+impl<T: LeafData> SVO<T> {
+    // The data and depth of the voxel containing the point, or None if the point is outside of
+    // the SVO. A point on the boundary between two voxels belongs to the upper one, see svo::index,
+    // and a point on the far faces of the unit cube belongs to the voxel at that face. So each voxel
+    // includes the faces at its origin, and its far faces only where they're on the cube's boundary.
+    pub fn voxel_at(&self, point: Vector3<f32>) -> Option<(T, u32)> {
+        voxel_at_octree(self, point)
+    }
+
+    // Every voxel that overlaps the box from min to max, as (origin, side length, data).
+    // The box includes its boundary, and voxels include the same faces as in voxel_at, so a box
+    // that just touches a voxel's far side inside the cube doesn't return it.
+    pub fn leaves_in_box(&self, min: Vector3<f32>, max: Vector3<f32>) -> LeavesInBox<T> {
+        LeavesInBox { leaves: Leaves::new(self, Some((min, max))) }
+    }
+}
+
+// See SVO::voxel_at, for any octree.
+pub fn voxel_at_octree<T, N: OctreeNode<T>>(root: N, point: Vector3<f32>) -> Option<(T, u32)> {
+    guard!(in_unit_cube(point));
+    let mut node = root;
+    let mut local_point = point;
+    let mut depth = 0;
+    loop {
+        match node.leaf_data() {
+            Some(data) => return Some((data, depth)),
+            None => {
+                let ix = index(local_point);
+                local_point = local_point * 2. - above_axis(ix);
+                node = node.child(ix);
+                depth += 1;
+            }
+        }
+    }
+}
+
+fn in_unit_cube(point: Vector3<f32>) -> bool {
+    let in_range = |f: f32| f >= 0. && f <= 1.;
+    in_range(point.x) && in_range(point.y) && in_range(point.z)
+}
+
+// Does the voxel from origin to origin + side_len overlap the box from min to max?
+// See SVO::voxel_at for which of the voxel's faces count.
+pub fn overlaps_box(origin: Vector3<f32>, side_len: f32, min: Vector3<f32>, max: Vector3<f32>) -> bool {
+    let overlaps = |o: f32, lo: f32, hi: f32| {
+        let end = o + side_len;
+        o <= hi && (lo < end || (end >= 1. && lo <= end))
+    };
+    overlaps(origin.x, min.x, max.x) && overlaps(origin.y, min.y, max.y) && overlaps(origin.z, min.z, max.z)
+}
+
+pub struct LeavesInBox<'a, T: 'a> {
+    leaves: Leaves<'a, T>,
+}
+
+impl<'a, T: LeafData> Iterator for LeavesInBox<'a, T> {
+    type Item = (Vector3<f32>, f32, T);
+
+    fn next(&mut self) -> Option<(Vector3<f32>, f32, T)> {
+        self.leaves.next().map(|leaf| (leaf.origin, leaf.side_len, *leaf.data))
+    }
+}
@@ -0,0 +1,84 @@
+use nalgebra::Vector3;
+use quickcheck::*;
+use svo::*;
+
+#[test]
+fn voxel_at_floor() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 3], VoxelData::new(2));
+
+    assert_eq!(svo.voxel_at(Vector3::new(0.1, 0.1, 0.1)), Some((VoxelData::new(1), 1)));
+    assert_eq!(svo.voxel_at(Vector3::new(0.9, 0.9, 0.9)), Some((VoxelData::new(0), 1)));
+    assert_eq!(svo.voxel_at(Vector3::new(0.8, 0.3, 0.1)), Some((VoxelData::new(2), 2)));
+    assert_eq!(svo.voxel_at(Vector3::new(0.6, 0.1, 0.1)), Some((VoxelData::new(1), 2)));
+
+    // Boundaries belong to the upper voxel.
+    assert_eq!(svo.voxel_at(Vector3::new(0.25, 0.5, 0.25)), Some((VoxelData::new(0), 1)));
+    assert_eq!(svo.voxel_at(Vector3::new(1., 1., 1.)), Some((VoxelData::new(0), 1)));
+
+    assert_eq!(svo.voxel_at(Vector3::new(1.5, 0.1, 0.1)), None);
+    assert_eq!(svo.voxel_at(Vector3::new(0.1, -0.1, 0.1)), None);
+}
+
+#[test]
+fn voxel_at_matches_get_block() {
+    fn check_voxel_at_matches_get_block(svo: SVO, x: u8, y: u8, z: u8) -> bool {
+        let coord = VoxelCoord::new((x % 16) as u32, (y % 16) as u32, (z % 16) as u32, 4);
+        let centre = coord.origin() + Vector3::new(0.5, 0.5, 0.5) * coord.side_len();
+        let (data, depth) = svo.voxel_at(centre).unwrap();
+        depth <= 4 && svo.get_block(coord) == Some(data)
+    }
+    quickcheck(check_voxel_at_matches_get_block as fn(SVO, u8, u8, u8) -> bool)
+}
+
+#[test]
+fn leaves_in_box_floor() {
+    let svo = SVO::floor();
+    let leaves: Vec<_> = svo.leaves_in_box(Vector3::new(0.1, 0.1, 0.1), Vector3::new(0.4, 0.6, 0.2)).collect();
+    assert_eq!(leaves, vec![
+        (Vector3::new(0., 0., 0.), 0.5, VoxelData::new(1)),
+        (Vector3::new(0., 0.5, 0.), 0.5, VoxelData::new(0))]);
+
+    // Touching the far side of the lower voxels doesn't count.
+    let leaves: Vec<_> = svo.leaves_in_box(Vector3::new(0.5, 0.5, 0.5), Vector3::new(1., 1., 1.)).collect();
+    assert_eq!(leaves, vec![(Vector3::new(0.5, 0.5, 0.5), 0.5, VoxelData::new(0))]);
+
+    assert_eq!(svo.leaves_in_box(Vector3::new(2., 2., 2.), Vector3::new(3., 3., 3.)).count(), 0);
+}
+
+#[test]
+fn leaves_in_box_far_faces() {
+    // The cube's far faces belong to the voxels at them, like in voxel_at.
+    let svo = SVO::floor();
+    let corner = Vector3::new(1., 1., 1.);
+    let leaves: Vec<_> = svo.leaves_in_box(corner, corner).collect();
+    assert_eq!(leaves, vec![(Vector3::new(0.5, 0.5, 0.5), 0.5, VoxelData::new(0))]);
+    assert_eq!(svo.voxel_at(corner), Some((VoxelData::new(0), 1)));
+
+    let edge = Vector3::new(1., 0.25, 0.25);
+    let leaves: Vec<_> = svo.leaves_in_box(edge, edge).collect();
+    assert_eq!(leaves, vec![(Vector3::new(0.5, 0., 0.), 0.5, VoxelData::new(1))]);
+}
+
+#[test]
+fn leaves_in_whole_cube() {
+    fn check_leaves_in_whole_cube(svo: SVO) -> bool {
+        let volume = svo.leaves_in_box(Vector3::new(0., 0., 0.), Vector3::new(1., 1., 1.))
+                        .map(|(_, side_len, _)| side_len * side_len * side_len)
+                        .fold(0., |x, y| x + y);
+        (volume - 1.).abs() < 0.0001
+    }
+    quickcheck(check_leaves_in_whole_cube as fn(SVO) -> bool)
+}
+
+#[test]
+fn leaves_in_box_contain_points() {
+    fn check_leaves_in_box_contain_points(svo: SVO, x: u8, y: u8, z: u8) -> bool {
+        // Dividing by 255 includes the far faces of the cube.
+        let point = Vector3::new(x as f32, y as f32, z as f32) / 255.;
+        let leaves: Vec<_> = svo.leaves_in_box(point, point).collect();
+        let (data, _) = svo.voxel_at(point).unwrap();
+        leaves.len() == 1 && leaves[0].2 == data
+    }
+    quickcheck(check_leaves_in_box_contain_points as fn(SVO, u8, u8, u8) -> bool)
+}
@@ -1,21 +1,116 @@
 use svo::voxel_data::VoxelData;
-use nalgebra::Vector3;
+use svo::{SVO, LeafData, offset};
+use nalgebra::{Vector3, zero};
+use std::collections::HashMap;
 
 pub type RegisterExtern = extern "stdcall" fn(Vector3<f32>, i32, VoxelData) -> u32;
 pub type DeregisterExtern = extern "stdcall" fn(u32);
 
-pub struct RegistrationFunctions<'a> {
-    pub register: Box<Fn(Vector3<f32>, i32, VoxelData) -> u32 + 'a>,
+// Called whenever a leaf appears or disappears, so that something outside of the SVO can keep a
+// copy of its leaves. register gets the leaf's origin in the unit cube, its depth and its data,
+// and returns the external id that the leaf stores. deregister gets that id back.
+// Only the _with methods and SVO::edit_with call these. Every other edit, and Clone, makes leaves
+// with the id 0, which means unregistered, so an SVO that's being mirrored should be edited
+// through the _with methods, or edit_with for edits that don't have one.
+// Since 0 means unregistered, register must never return it, or the leaf gets registered again by
+// every edit_with and the earlier registrations are never deregistered. Only blank returns 0.
+pub struct RegistrationFunctions<'a, T = VoxelData> {
+    pub register: Box<Fn(Vector3<f32>, i32, T) -> u32 + 'a>,
     pub deregister: Box<Fn(u32) + 'a>
 }
 
+impl<'a, T> RegistrationFunctions<'a, T> {
+	// Does nothing, and gives every leaf the id 0.
+	pub fn blank() -> RegistrationFunctions<'a, T> {
+		RegistrationFunctions {
+			register: Box::new(|_, _, _| 0),
+			deregister: Box::new(|_| {})
+		}
+	}
+}
+
 impl<'a> RegistrationFunctions<'a> {
 	pub fn external(
 			ext_register: RegisterExtern,
 		    ext_deregister: DeregisterExtern) -> RegistrationFunctions<'a> {
 		RegistrationFunctions {
-			register: Box::new(move |origin, depth, data| ext_register(origin, depth, data)),
+			register: Box::new(move |origin, depth, data| {
+				let external_id = ext_register(origin, depth, data);
+				assert!(external_id != 0, "External register returned 0, which means unregistered");
+				external_id
+			}),
 			deregister: Box::new(move |external_id| ext_deregister(external_id))
 		}
 	}
 }
+
+impl<T: LeafData> SVO<T> {
+	// Run an edit that doesn't know about registration, like fill_box, apply_brush, stamp or
+	// EditHistory::undo, then deregister the leaves it removed and register the ones it made.
+	// Leaves that are still at the same path with the same data keep their ids.
+	// This walks the whole tree twice and keeps a map of every registered leaf, so it's O(n) in the
+	// size of the tree however small the edit is. The _with methods only touch what they change.
+	pub fn edit_with<F, R>(&mut self, registration: &RegistrationFunctions<T>, edit: F) -> R
+			where F: FnOnce(&mut SVO<T>) -> R {
+		let mut before = Vec::new();
+		self.collect_ids(&mut Vec::new(), &mut before);
+		let mut unclaimed: HashMap<u32, (Vec<u8>, T)> = before.iter().cloned().map(|(id, index, data)| (id, (index, data))).collect();
+
+		let result = edit(self);
+
+		self.claim_ids(&mut Vec::new(), &mut unclaimed);
+		for &(id, _, _) in &before {
+			if unclaimed.remove(&id).is_some() { (registration.deregister)(id) }
+		}
+		self.register_unregistered(registration, zero(), 0);
+		result
+	}
+
+	// Every registered leaf's id, path and data, in index order.
+	fn collect_ids(&self, index: &mut Vec<u8>, ids: &mut Vec<(u32, Vec<u8>, T)>) {
+		match *self {
+			SVO::Voxel { external_id: 0, .. } => {},
+			SVO::Voxel { data, external_id } => ids.push((external_id, index.clone(), data)),
+			SVO::Octants(ref octants) => for ix in 0..8 {
+				index.push(ix);
+				octants[ix as usize].collect_ids(index, ids);
+				index.pop();
+			},
+		}
+	}
+
+	// Keep the ids of leaves that haven't moved or changed, and clear the rest.
+	fn claim_ids(&mut self, index: &mut Vec<u8>, unclaimed: &mut HashMap<u32, (Vec<u8>, T)>) {
+		match *self {
+			SVO::Voxel { data, ref mut external_id } => {
+				let id = *external_id;
+				let unchanged = unclaimed.get(&id).map_or(false, |&(ref old_index, old_data)| {
+					old_index == index && old_data == data
+				});
+				if unchanged {
+					unclaimed.remove(&id);
+				} else {
+					*external_id = 0;
+				}
+			},
+			SVO::Octants(ref mut octants) => for ix in 0..8 {
+				index.push(ix);
+				octants[ix as usize].claim_ids(index, unclaimed);
+				index.pop();
+			},
+		}
+	}
+
+	// Register every leaf in this subtree that has the id 0, for example after replacing it with a
+	// copy. The origin and depth are where it is in the SVO that registration is tracking.
+	pub fn register_unregistered(&mut self, registration: &RegistrationFunctions<T>, origin: Vector3<f32>, depth: i32) {
+		match *self {
+			SVO::Voxel { data, ref mut external_id } => if *external_id == 0 {
+				*external_id = (registration.register)(origin, depth, data);
+			},
+			SVO::Octants(ref mut octants) => for ix in 0..8 {
+				octants[ix as usize].register_unregistered(registration, origin + offset(ix, depth), depth + 1);
+			},
+		}
+	}
+}
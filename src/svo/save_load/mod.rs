@@ -1,20 +1,20 @@
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::hash::Hash;
 use std::io::{Read, Write, Result, Error, ErrorKind};
 use svo::*;
 
 #[cfg(test)]
 mod test;
 
-const VOXEL_TAG: u8 = 1;
-const OCTANT_TAG: u8 = 2;
+pub const VOXEL_TAG: u8 = 1;
+pub const OCTANT_TAG: u8 = 2;
 
 pub trait ReadSVO: Read {
-    fn read_voxel_data(&mut self) -> Result<VoxelData> {
-        let voxel_type = try! { self.read_i32::<LittleEndian>() };
-        Ok(VoxelData::new(voxel_type))
+    fn read_voxel_data<T: LeafData>(&mut self) -> Result<T> {
+        T::read_from(self)
     }
 
-    fn read_svo(&mut self) -> Result<SVO> {
+    fn read_svo<T: LeafData>(&mut self) -> Result<SVO<T>> {
         let mut b = [0];
         let bytes_read = try!{ self.read(&mut b) };
         if bytes_read == 0 {
@@ -35,15 +35,22 @@ pub trait ReadSVO: Read {
             }
         }
     }
+
+    fn read_arena_svo<T: LeafData>(&mut self) -> Result<ArenaSVO<T>> {
+        ArenaSVO::read_from(self)
+    }
+
+    fn read_dag_svo<T: LeafData + Eq + Hash>(&mut self) -> Result<DagSVO<T>> {
+        DagSVO::read_from(self)
+    }
 }
 
 pub trait WriteSVO: Write {
-    fn write_voxel(&mut self, voxel: VoxelData) -> Result<()> {
-        let VoxelData { voxel_type } = voxel;
-        self.write_i32::<LittleEndian>(voxel_type)
+    fn write_voxel<T: LeafData>(&mut self, voxel: T) -> Result<()> {
+        voxel.write_to(self)
     }
 
-    fn write_svo(&mut self, svo: &SVO) -> Result<()> {
+    fn write_svo<T: LeafData>(&mut self, svo: &SVO<T>) -> Result<()> {
         match *svo {
             SVO::Voxel { data, .. } => {
                 try!{ self.write(&[VOXEL_TAG]) };
@@ -57,6 +64,14 @@ pub trait WriteSVO: Write {
             }
         }
     }
+
+    fn write_arena_svo<T: LeafData>(&mut self, svo: &ArenaSVO<T>) -> Result<()> {
+        svo.write_to(self)
+    }
+
+    fn write_dag_svo<T: LeafData + Eq + Hash>(&mut self, svo: &DagSVO<T>) -> Result<()> {
+        svo.write_to(self)
+    }
 }
 
 // Yes these are weird, but they really need to be here! Things don't implement ReadSVO by default!
@@ -1,6 +1,7 @@
 use svo::*;
 use svo::save_load::{ReadSVO, WriteSVO};
-use std::io::Cursor;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Cursor, Read, Write, Result};
 
 #[test]
 fn save_load() {
@@ -35,7 +36,7 @@ fn save_load() {
 
     println!("==== LOADING ====");
     let reader = &mut Cursor::new(bytes);
-    let new_svo = reader.read_svo().unwrap();
+    let new_svo: SVO = reader.read_svo().unwrap();
     new_svo.assert_contains(vec![
         (0. , 0. , 0. , 1, 1),
             (0.5 , 0.  , 0.  , 2, 1),
@@ -53,3 +54,49 @@ fn save_load() {
         (0. , 0.5, 0.5, 1, 0),
         (0.5, 0.5, 0.5, 1, 0)]);
 }
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+struct Density {
+    material: u8,
+    density: f32,
+}
+
+impl LeafData for Density {
+    fn is_empty(&self) -> bool {
+        self.density <= 0.
+    }
+
+    fn empty() -> Density {
+        Density { material: 0, density: 0. }
+    }
+
+    fn read_from<R: Read + ?Sized>(reader: &mut R) -> Result<Density> {
+        let material = try! { reader.read_u8() };
+        let density = try! { reader.read_f32::<LittleEndian>() };
+        Ok(Density { material: material, density: density })
+    }
+
+    fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()> {
+        try! { writer.write_u8(self.material) };
+        writer.write_f32::<LittleEndian>(self.density)
+    }
+}
+
+#[test]
+fn save_load_custom_leaf_data() {
+    let air = Density { material: 0, density: 0. };
+    let rock = Density { material: 3, density: 0.75 };
+    let mut svo = SVO::new_voxel(air);
+    svo.set_block(&[0, 5], rock);
+    svo.set_block(&[6], rock);
+
+    let mut bytes: Vec<u8> = vec![];
+    bytes.write_svo(&svo).unwrap();
+    let new_svo: SVO<Density> = Cursor::new(bytes).read_svo().unwrap();
+    assert_eq!(new_svo, svo);
+
+    // Setting the same data back recombines just like VoxelData does.
+    svo.set_block(&[0, 5], air);
+    svo.set_block(&[6], air);
+    assert_eq!(svo, SVO::new_voxel(air));
+}
@@ -1,24 +1,37 @@
 #[cfg(test)]
 mod test;
 
+use nalgebra::{Vector3, zero};
 use svo::*;
-use arrayvec::ArrayVec;
 
-impl SVO {
+impl<T: LeafData> SVO<T> {
     // Follow an index, splitting voxels as necessary. The set the block at the target to a Voxel with the specified data.
     // Then go back up the tree, recombining if we've transformed all the octants in a node to the same voxel.
     pub fn set_block(
             &mut self,
             index: &[u8],
-            new_data: VoxelData) {
+            new_data: T) {
 
-        self.set_voxel_from(index, &new_data);
+        self.set_block_with(&RegistrationFunctions::blank(), index, new_data);
+    }
+
+    // Like set_block, but every leaf that appears or disappears along the way is registered or deregistered.
+    pub fn set_block_with(
+            &mut self,
+            registration: &RegistrationFunctions<T>,
+            index: &[u8],
+            new_data: T) {
+
+        self.set_voxel_from(registration, index, &new_data, zero(), 0);
     }
 
     fn set_voxel_from(
             &mut self,
+            registration: &RegistrationFunctions<T>,
             index: &[u8],
-            new_data: &VoxelData) {
+            new_data: &T,
+            origin: Vector3<f32>,
+            depth: i32) {
 
         if let Some(voxel_data) = self.get_voxel_data() {
             if voxel_data == *new_data {return;} // nothing to do
@@ -26,41 +39,74 @@ impl SVO {
 
         match index.split_first() {
             // Overwrite whatever's here with the new voxel.
-            None => *self = SVO::new_voxel(*new_data),
+            None => {
+                self.deregister_all(registration);
+                *self = SVO::new_registered_voxel(registration, *new_data, origin, depth);
+            },
 
             // We need to go deeper.
             Some((&ix, rest)) => {
                 // Voxels get split up
                 if self.get_voxel_data().is_some() {
-                    self.subdivide_voxel();
+                    self.subdivide_voxel_with(registration, origin, depth);
                 }
 
                 // Insert destructively into the sub_octant
                 if let SVO::Octants(ref mut octants) = *self {
-                    octants[ix as usize].set_voxel_from(rest, new_data);
+                    let child_origin = origin + offset(ix, depth);
+                    octants[ix as usize].set_voxel_from(registration, rest, new_data, child_origin, depth + 1);
                 };
 
-                self.recombine_svo();
+                self.recombine_svo_with(registration, origin, depth);
             }
         }
     }
 
-    fn subdivide_voxel(&mut self) {
-        *self = match *self {
-            SVO::Octants(_) => panic!("subdivide_voxel called on a non-voxel!"),
-            SVO::Voxel { data, .. } => SVO::new_octants(|_| { SVO::new_voxel(data) })
-        };
+    // A voxel with the id that registering it gave back.
+    pub fn new_registered_voxel(
+            registration: &RegistrationFunctions<T>,
+            data: T,
+            origin: Vector3<f32>,
+            depth: i32) -> SVO<T> {
+
+        SVO::Voxel { data: data, external_id: (registration.register)(origin, depth, data) }
     }
 
+    // Deregister every leaf in this subtree.
+    pub fn deregister_all(&self, registration: &RegistrationFunctions<T>) {
+        match *self {
+            SVO::Voxel { external_id: 0, .. } => {}, // never registered
+            SVO::Voxel { external_id, .. } => (registration.deregister)(external_id),
+            SVO::Octants(ref octants) => for octant in octants.iter() { octant.deregister_all(registration) },
+        }
+    }
+
+    // Split a voxel into eight copies of itself, deregistering it and registering the copies.
+    // The origin and depth are where this voxel is in the SVO that registration is tracking.
+    pub fn subdivide_voxel_with(&mut self, registration: &RegistrationFunctions<T>, origin: Vector3<f32>, depth: i32) {
+        let data = self.get_voxel_data().expect("subdivide_voxel_with called on a non-voxel!");
+        self.deregister_all(registration);
+        *self = SVO::new_octants(|ix| {
+            SVO::new_registered_voxel(registration, data, origin + offset(ix, depth), depth + 1)
+        });
+    }
+
+    // The new voxel isn't registered, see SVO::edit_with.
     pub fn recombine_svo(&mut self) {
-    if let Some(combined_voxel_data) = self.get_octants().and_then(combine_voxels) {
-            *self = SVO::new_voxel(combined_voxel_data);
+        self.recombine_svo_with(&RegistrationFunctions::blank(), zero(), 0);
+    }
+
+    // Like recombine_svo, but the octants are deregistered and the new voxel registered.
+    pub fn recombine_svo_with(&mut self, registration: &RegistrationFunctions<T>, origin: Vector3<f32>, depth: i32) {
+        if let Some(combined_voxel_data) = self.get_octants().and_then(combine_voxels) {
+            self.deregister_all(registration);
+            *self = SVO::new_registered_voxel(registration, combined_voxel_data, origin, depth);
         }
     }
 }
 
 // Return the voxel_data that all of the octants share, or None.
-fn combine_voxels(octants: &ArrayVec<[Box<SVO>; 8]>) -> Option<VoxelData> {
+fn combine_voxels<T: LeafData>(octants: &SubOctants<T>) -> Option<T> {
         octants[0].get_voxel_data().and_then(|voxel_data| {
             let first_data = Some(voxel_data);
             for octant in &octants[1..] { guard!(octant.get_voxel_data() == first_data) }
@@ -1,3 +1,7 @@
+use nalgebra::{Vector3, zero};
+use quickcheck::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use svo::*;
 
 #[test]
@@ -49,4 +53,207 @@ fn setting_blocks() {
         (0.5, 0. , 0.5, 1, 1),
         (0. , 0.5, 0.5, 1, 0),
         (0.5, 0.5, 0.5, 1, 0)]);
-}
\ No newline at end of file
+}
+// Every leaf with its external id, origin and depth, in index order.
+fn registered_leaves(svo: &SVO, origin: Vector3<f32>, depth: i32, leaves: &mut Vec<(u32, Vector3<f32>, i32, VoxelData)>) {
+    match *svo {
+        SVO::Voxel { data, external_id } => leaves.push((external_id, origin, depth, data)),
+        SVO::Octants(ref octants) => for ix in 0..8 {
+            registered_leaves(&octants[ix as usize], origin + offset(ix, depth), depth + 1, leaves);
+        },
+    }
+}
+
+// Registers leaves with increasing ids, keeping track of the ones that haven't been deregistered.
+fn check_registration(edits: &[(Vec<u8>, VoxelData)]) -> bool {
+    check_registered_edits(|svo, registration| for &(ref index, data) in edits {
+        svo.set_block_with(registration, index, data);
+    })
+}
+
+fn check_registered_edits<F>(edit: F) -> bool where F: FnOnce(&mut SVO, &RegistrationFunctions) {
+    let live = RefCell::new(HashMap::new());
+    let next_id = Cell::new(1);
+    let registration = RegistrationFunctions {
+        register: Box::new(|origin, depth, data| {
+            let id = next_id.get();
+            next_id.set(id + 1);
+            live.borrow_mut().insert(id, (origin, depth, data));
+            id
+        }),
+        deregister: Box::new(|id| assert!(live.borrow_mut().remove(&id).is_some(), "Deregistered {} twice", id)),
+    };
+
+    let mut svo = SVO::new_registered_voxel(&registration, VoxelData::new(0), zero(), 0);
+    edit(&mut svo, &registration);
+
+    let mut leaves = Vec::new();
+    registered_leaves(&svo, zero(), 0, &mut leaves);
+    let live = live.borrow();
+    leaves.len() == live.len() && leaves.iter().all(|&(id, origin, depth, data)| {
+        live.get(&id) == Some(&(origin, depth, data))
+    })
+}
+
+#[test]
+fn registers_leaves() {
+    assert!(check_registration(&[
+        (vec![1, 3], VoxelData::new(1)),
+        (vec![1, 3, 6], VoxelData::new(2)),
+        (vec![2], VoxelData::new(1)),
+    ]));
+}
+
+#[test]
+fn deregisters_recombined_leaves() {
+    let edits: Vec<_> = (0..8).map(|ix| (vec![4, ix], VoxelData::new(1))).collect();
+    assert!(check_registration(&edits));
+}
+
+#[test]
+fn registered_leaves_match_svo() {
+    fn check_random_edits(edits: Vec<(u8, u8, bool)>) -> bool {
+        let edits: Vec<_> = edits.iter().map(|&(a, b, solid)| {
+            (vec![a % 8, b % 8], VoxelData::new(solid as i32))
+        }).collect();
+        check_registration(&edits)
+    }
+    quickcheck(check_random_edits as fn(Vec<(u8, u8, bool)>) -> bool)
+}
+
+#[test]
+fn unregistered_edits_have_no_ids() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 3], VoxelData::new(3));
+    let mut leaves = Vec::new();
+    registered_leaves(&svo, zero(), 0, &mut leaves);
+    assert!(leaves.iter().all(|&(id, _, _, _)| id == 0));
+}
+
+#[test]
+fn edit_with_registers_plain_edits() {
+    fn check_plain_edits(edits: Vec<(u8, u8, u8)>) -> bool {
+        check_registered_edits(|svo, registration| {
+            let mut history = EditHistory::new(1 << 20);
+            for &(a, b, kind) in &edits {
+                let data = VoxelData::new((kind >> 2 & 1) as i32);
+                let (a, b) = (VoxelCoord::new((a % 4) as u32, (a / 4 % 4) as u32, (a / 16 % 4) as u32, 2),
+                              VoxelCoord::new((b % 4) as u32, (b / 4 % 4) as u32, (b / 16 % 4) as u32, 2));
+                match kind % 4 {
+                    0 => { svo.edit_with(registration, |svo| history.record(svo, &[], |svo| svo.fill_coords(a, b, data))); },
+                    1 => { svo.edit_with(registration, |svo| history.undo(svo)); },
+                    2 => { svo.edit_with(registration, |svo| history.redo(svo)); },
+                    _ => {
+                        let copy = svo.clone();
+                        svo.edit_with(registration, |svo| svo.stamp(&a.to_index(), &copy, StampMode::Replace));
+                    },
+                }
+            }
+        })
+    }
+    quickcheck(check_plain_edits as fn(Vec<(u8, u8, u8)>) -> bool)
+}
+
+#[test]
+fn edit_with_keeps_unchanged_ids() {
+    let next_id = Cell::new(1);
+    let deregistered = RefCell::new(Vec::new());
+    let registration = RegistrationFunctions {
+        register: Box::new(|_, _, _| { next_id.set(next_id.get() + 1); next_id.get() - 1 }),
+        deregister: Box::new(|id| deregistered.borrow_mut().push(id)),
+    };
+    let mut svo = SVO::new_registered_voxel(&registration, VoxelData::new(0), zero(), 0);
+    svo.set_block_with(&registration, &[3], VoxelData::new(1));
+    // The root was split into octants 2 to 9, and then octant 3 replaced by 10.
+    assert_eq!(*deregistered.borrow(), vec![1, 5]);
+
+    svo.edit_with(&registration, |svo| svo.set_block(&[5], VoxelData::new(1)));
+    assert_eq!(*deregistered.borrow(), vec![1, 5, 7]);
+    let mut leaves = Vec::new();
+    registered_leaves(&svo, zero(), 0, &mut leaves);
+    let ids: Vec<_> = leaves.iter().map(|&(id, _, _, _)| id).collect();
+    assert_eq!(ids, vec![2, 3, 4, 10, 6, 11, 8, 9]);
+}
+
+#[test]
+fn clones_are_unregistered() {
+    let next_id = Cell::new(1);
+    let registration = RegistrationFunctions {
+        register: Box::new(|_, _, _| { next_id.set(next_id.get() + 1); next_id.get() - 1 }),
+        deregister: Box::new(|_| {}),
+    };
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block_with(&registration, &[3, 1], VoxelData::new(1));
+    let mut leaves = Vec::new();
+    registered_leaves(&svo.clone(), zero(), 0, &mut leaves);
+    assert!(leaves.iter().all(|&(id, _, _, _)| id == 0));
+}
+
+#[test]
+fn registered_trees_equal_their_clones() {
+    let next_id = Cell::new(1);
+    let registration = RegistrationFunctions {
+        register: Box::new(|_, _, _| { next_id.set(next_id.get() + 1); next_id.get() - 1 }),
+        deregister: Box::new(|_| {}),
+    };
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block_with(&registration, &[3, 1], VoxelData::new(1));
+    assert_eq!(svo.clone(), svo);
+    assert!(svo.clone() != SVO::new_voxel(VoxelData::new(0)));
+}
+
+#[test]
+fn with_methods_register_their_leaves() {
+    fn check_with_methods(edits: Vec<(u8, u8, u8)>) -> bool {
+        let mut plain = SVO::new_voxel(VoxelData::new(0));
+        let mut registered = None;
+        let all_registered = check_registered_edits(|svo, registration| {
+            for &(a, b, kind) in &edits {
+                let data = VoxelData::new((kind >> 3 & 1) as i32);
+                let (a, b) = (VoxelCoord::new((a % 4) as u32, (a / 4 % 4) as u32, (a / 16 % 4) as u32, 2),
+                              VoxelCoord::new((b % 4) as u32, (b / 4 % 4) as u32, (b / 16 % 4) as u32, 2));
+                let centre = Vector3::new(a.x as f32, a.y as f32, a.z as f32) * 0.25;
+                let sphere = Sphere { centre: centre, radius: (b.x + 1) as f32 * 0.1 };
+                let stamp = SVO::new_octants(|ix| SVO::new_voxel(VoxelData::new((ix as i32 + b.y as i32) % 2)));
+                match kind % 8 {
+                    0 => {
+                        svo.fill_coords_with(registration, a, b, data);
+                        plain.fill_coords(a, b, data);
+                    },
+                    1 => {
+                        let (min, max) = (centre, centre + Vector3::new(0.3, 0.3, 0.3));
+                        svo.fill_box_with(registration, min, max, 3, data);
+                        plain.fill_box(min, max, 3, data);
+                    },
+                    2 => {
+                        svo.apply_brush_with(registration, &sphere, BrushOp::Union(data), 3);
+                        plain.apply_brush(&sphere, BrushOp::Union(data), 3);
+                    },
+                    3 => {
+                        svo.apply_brush_with(registration, &sphere, BrushOp::Subtract, 3);
+                        plain.apply_brush(&sphere, BrushOp::Subtract, 3);
+                    },
+                    4 => {
+                        svo.apply_brush_with(registration, &sphere, BrushOp::Paint(VoxelData::new(2)), 3);
+                        plain.apply_brush(&sphere, BrushOp::Paint(VoxelData::new(2)), 3);
+                    },
+                    5 => {
+                        svo.stamp_with(registration, &a.to_index(), &stamp, StampMode::Replace);
+                        plain.stamp(&a.to_index(), &stamp, StampMode::Replace);
+                    },
+                    6 => {
+                        svo.stamp_with(registration, &a.to_index(), &stamp, StampMode::Overlay);
+                        plain.stamp(&a.to_index(), &stamp, StampMode::Overlay);
+                    },
+                    _ => {
+                        svo.merge_into_with(registration, &stamp, &|a, b| MergeMode::Union.combine(a, b), zero(), 0);
+                        plain = plain.merge_with(&stamp, MergeMode::Union);
+                    },
+                }
+            }
+            registered = Some(svo.clone());
+        });
+        all_registered && registered == Some(plain)
+    }
+    quickcheck(check_with_methods as fn(Vec<(u8, u8, u8)>) -> bool)
+}
@@ -0,0 +1,72 @@
+use nalgebra::{Vector3, zero};
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// How a stamped SVO is combined with what's already there.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StampMode {
+    // Everything under the stamp is replaced, including with its empty voxels.
+    Replace,
+    // Only the stamp's non-empty voxels are copied, see MergeMode::Overlay.
+    Overlay,
+}
+
+impl<T: LeafData> SVO<T> {
+    // A copy of the subtree at the index path. If the path runs into a bigger voxel first, the
+    // copy is a single voxel of that data.
+    pub fn extract(&self, index: &[u8]) -> SVO<T> {
+        match (self, index.split_first()) {
+            (&SVO::Octants(ref octants), Some((&ix, rest))) => octants[ix as usize].extract(rest),
+            _ => self.clone(),
+        }
+    }
+
+    pub fn extract_at(&self, coord: VoxelCoord) -> SVO<T> {
+        self.extract(&coord.to_index())
+    }
+
+    // Put a copy of the stamp in place of the subtree at the index path, scaled to fit it.
+    // Bigger voxels along the path are split up, and the result is recombined.
+    pub fn stamp(&mut self, index: &[u8], stamp: &SVO<T>, mode: StampMode) {
+        self.stamp_with(&RegistrationFunctions::blank(), index, stamp, mode);
+    }
+
+    // Like stamp, but every leaf that appears or disappears is registered or deregistered.
+    pub fn stamp_with(&mut self, registration: &RegistrationFunctions<T>, index: &[u8], stamp: &SVO<T>, mode: StampMode) {
+        self.stamp_helper(registration, index, stamp, mode, zero(), 0);
+    }
+
+    fn stamp_helper(&mut self,
+                    registration: &RegistrationFunctions<T>,
+                    index: &[u8],
+                    stamp: &SVO<T>,
+                    mode: StampMode,
+                    origin: Vector3<f32>,
+                    depth: i32) {
+        match index.split_first() {
+            None => match mode {
+                StampMode::Replace => {
+                    self.deregister_all(registration);
+                    *self = stamp.clone();
+                    self.register_unregistered(registration, origin, depth);
+                },
+                StampMode::Overlay => self.merge_into_with(registration, stamp, &|a, b| MergeMode::Overlay.combine(a, b), origin, depth),
+            },
+            Some((&ix, rest)) => {
+                if self.get_voxel_data().is_some() {
+                    self.subdivide_voxel_with(registration, origin, depth);
+                }
+                if let SVO::Octants(ref mut octants) = *self {
+                    octants[ix as usize].stamp_helper(registration, rest, stamp, mode, origin + offset(ix, depth), depth + 1);
+                }
+                self.recombine_svo_with(registration, origin, depth);
+            }
+        }
+    }
+
+    pub fn stamp_at(&mut self, coord: VoxelCoord, stamp: &SVO<T>, mode: StampMode) {
+        self.stamp(&coord.to_index(), stamp, mode);
+    }
+}
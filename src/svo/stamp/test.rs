@@ -0,0 +1,79 @@
+use quickcheck::*;
+use std::io::Cursor;
+use svo::*;
+use svo::save_load::{ReadSVO, WriteSVO};
+
+// A two voxel tall pillar in octant 0, with a different block on top.
+fn pillar() -> SVO {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[0, 0], VoxelData::new(1));
+    svo.set_block(&[0, 2], VoxelData::new(2));
+    svo
+}
+
+#[test]
+fn extract_subtree() {
+    let svo = pillar();
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    expected.set_block(&[0], VoxelData::new(1));
+    expected.set_block(&[2], VoxelData::new(2));
+    assert_eq!(svo.extract(&[0]), expected);
+    assert_eq!(svo.extract(&[]), svo);
+    assert_eq!(svo.extract_at(VoxelCoord::new(0, 1, 0, 2)), SVO::new_voxel(VoxelData::new(2)));
+}
+
+#[test]
+fn extract_inside_bigger_voxel() {
+    let svo = pillar();
+    assert_eq!(svo.extract(&[7, 3, 1]), SVO::new_voxel(VoxelData::new(0)));
+}
+
+#[test]
+fn stamp_replace() {
+    let mut svo = SVO::floor();
+    svo.stamp(&[3], &pillar().extract(&[0]), StampMode::Replace);
+    assert_eq!(svo.extract(&[3]), pillar().extract(&[0]));
+    assert_eq!(svo.extract(&[0]), SVO::new_voxel(VoxelData::new(1)));
+
+    // Stamping the same data back recombines.
+    svo.stamp(&[3], &SVO::new_voxel(VoxelData::new(0)), StampMode::Replace);
+    assert_eq!(svo, SVO::floor());
+}
+
+#[test]
+fn stamp_overlay() {
+    let mut svo = SVO::floor();
+    svo.stamp(&[], &pillar(), StampMode::Overlay);
+    let mut expected = SVO::floor();
+    expected.set_block(&[0, 2], VoxelData::new(2));
+    assert_eq!(svo, expected);
+
+    // Empty voxels in the stamp don't clear anything.
+    let mut svo = SVO::floor();
+    svo.stamp(&[0], &SVO::new_voxel(VoxelData::new(0)), StampMode::Overlay);
+    assert_eq!(svo, SVO::floor());
+}
+
+#[test]
+fn stamp_into_bigger_voxel() {
+    let mut svo = SVO::new_voxel(VoxelData::new(1));
+    svo.stamp_at(VoxelCoord::new(3, 3, 3, 2), &pillar(), StampMode::Replace);
+    assert_eq!(svo.extract(&[7, 7]), pillar());
+    assert_eq!(svo.extract(&[7, 6]), SVO::new_voxel(VoxelData::new(1)));
+    assert_eq!(svo.extract(&[0]), SVO::new_voxel(VoxelData::new(1)));
+}
+
+#[test]
+fn stamps_round_trip_through_files() {
+    fn check_round_trip(source: SVO, target: SVO, ix: u8) -> bool {
+        let index = [ix % 8];
+        let mut bytes = Vec::new();
+        bytes.write_svo(&source.extract(&index)).unwrap();
+        let loaded: SVO = Cursor::new(bytes).read_svo().unwrap();
+
+        let mut stamped = target.clone();
+        stamped.stamp(&index, &loaded, StampMode::Replace);
+        stamped.extract(&index) == source.extract(&index)
+    }
+    quickcheck(check_round_trip as fn(SVO, SVO, u8) -> bool)
+}
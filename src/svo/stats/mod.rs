@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::size_of;
+use svo::*;
+use svo::traverse::Position;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvoStats<T = VoxelData> {
+    // Nodes that are split into octants.
+    pub node_count: usize,
+    pub leaf_count: usize,
+    // leaf_depths[d] is the number of leaves at depth d.
+    pub leaf_depths: Vec<usize>,
+    // The number of leaves with each distinct data, in the order they're first found.
+    pub data_counts: Vec<(T, usize)>,
+    pub max_depth: u32,
+    // Every node but the root is boxed.
+    pub heap_bytes: usize,
+    // The length of the output of WriteSVO::write_svo.
+    pub serialized_bytes: usize,
+}
+
+impl<T: LeafData> SVO<T> {
+    pub fn stats(&self) -> SvoStats<T> {
+        let mut collector = StatsCollector {
+            stats: SvoStats {
+                node_count: 0,
+                leaf_count: 0,
+                leaf_depths: Vec::new(),
+                data_counts: Vec::new(),
+                max_depth: 0,
+                heap_bytes: 0,
+                serialized_bytes: 0,
+            },
+            data_positions: HashMap::new(),
+            bytes: Vec::new(),
+        };
+        self.visit(&mut collector);
+        let mut stats = collector.stats;
+        stats.heap_bytes = (stats.node_count + stats.leaf_count - 1) * size_of::<SVO<T>>();
+        stats
+    }
+}
+
+impl<T: LeafData> SvoStats<T> {
+    // The number of leaves with exactly this data.
+    pub fn count_of(&self, data: &T) -> usize {
+        self.data_counts.iter().find(|&&(ref d, _)| d == data).map_or(0, |&(_, count)| count)
+    }
+}
+
+// Builds up the stats in one walk over the SVO.
+struct StatsCollector<T> {
+    stats: SvoStats<T>,
+    // The position in data_counts of each distinct data, keyed by its serialized bytes, since leaf
+    // data is only PartialEq and can be anything from a handful of voxel types to floats.
+    data_positions: HashMap<Vec<u8>, usize>,
+    // The current leaf's serialized bytes, kept to save allocating for every leaf.
+    bytes: Vec<u8>,
+}
+
+impl<T: LeafData> SvoVisitor<T> for StatsCollector<T> {
+    fn enter(&mut self, _position: &Position) -> bool {
+        self.stats.node_count += 1;
+        self.stats.serialized_bytes += 1;
+        true
+    }
+
+    fn visit_leaf(&mut self, position: &Position, data: &T) {
+        let stats = &mut self.stats;
+        let depth = position.depth();
+        stats.leaf_count += 1;
+        if stats.leaf_depths.len() <= depth as usize {
+            stats.leaf_depths.resize(depth as usize + 1, 0);
+        }
+        stats.leaf_depths[depth as usize] += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+
+        self.bytes.clear();
+        data.write_to(&mut self.bytes).expect("Writing to a Vec can't fail");
+        stats.serialized_bytes += 1 + self.bytes.len();
+        if let Some(&position) = self.data_positions.get(&self.bytes) {
+            stats.data_counts[position].1 += 1;
+        } else {
+            self.data_positions.insert(self.bytes.clone(), stats.data_counts.len());
+            stats.data_counts.push((*data, 1));
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for SvoStats<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} nodes, {} leaves, max depth {}, {} heap bytes, {} serialized bytes, leaves by depth {:?}, leaves by data {:?}",
+               self.node_count, self.leaf_count, self.max_depth, self.heap_bytes, self.serialized_bytes,
+               self.leaf_depths, self.data_counts)
+    }
+}
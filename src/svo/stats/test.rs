@@ -0,0 +1,61 @@
+use quickcheck::*;
+use svo::*;
+use svo::save_load::WriteSVO;
+use std::mem::size_of;
+
+#[test]
+fn voxel_stats() {
+    let stats = SVO::new_voxel(VoxelData::new(3)).stats();
+    assert_eq!(stats.node_count, 0);
+    assert_eq!(stats.leaf_count, 1);
+    assert_eq!(stats.leaf_depths, vec![1]);
+    assert_eq!(stats.count_of(&VoxelData::new(3)), 1);
+    assert_eq!(stats.max_depth, 0);
+    assert_eq!(stats.heap_bytes, 0);
+    assert_eq!(stats.serialized_bytes, 5);
+}
+
+#[test]
+fn floor_stats() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 6], VoxelData::new(2));
+    let stats = svo.stats();
+    assert_eq!(stats.node_count, 2);
+    assert_eq!(stats.leaf_count, 15);
+    assert_eq!(stats.leaf_depths, vec![0, 7, 8]);
+    assert_eq!(stats.count_of(&VoxelData::new(0)), 4);
+    assert_eq!(stats.count_of(&VoxelData::new(1)), 10);
+    assert_eq!(stats.count_of(&VoxelData::new(2)), 1);
+    assert_eq!(stats.max_depth, 2);
+    assert_eq!(stats.heap_bytes, 16 * size_of::<SVO>());
+}
+
+#[test]
+fn serialized_size_matches_writer() {
+    fn check_serialized_size(svo: SVO) -> bool {
+        let mut bytes = Vec::new();
+        bytes.write_svo(&svo).unwrap();
+        let stats = svo.stats();
+        stats.serialized_bytes == bytes.len() &&
+            stats.leaf_depths.iter().sum::<usize>() == stats.leaf_count &&
+            stats.data_counts.iter().map(|&(_, count)| count).sum::<usize>() == stats.leaf_count
+    }
+    quickcheck(check_serialized_size as fn(SVO) -> bool)
+}
+
+#[test]
+fn data_counts_in_visit_order() {
+    let mut svo = SVO::new_voxel(VoxelData::new(5));
+    svo.set_block(&[3], VoxelData::new(2));
+    let stats = svo.stats();
+    assert_eq!(stats.data_counts, vec![(VoxelData::new(5), 7), (VoxelData::new(2), 1)]);
+    assert_eq!(stats.count_of(&VoxelData::new(9)), 0);
+}
+
+#[test]
+fn many_distinct_data() {
+    let svo = SVO::new_octants(|i| SVO::new_octants(|j| SVO::new_voxel(VoxelData::new((i * 8 + j) as i32))));
+    let stats = svo.stats();
+    let expected: Vec<_> = (0..64).map(|data| (VoxelData::new(data), 1)).collect();
+    assert_eq!(stats.data_counts, expected);
+}
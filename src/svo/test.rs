@@ -1,17 +1,8 @@
 use quickcheck::*;
 
-use nalgebra::{ApproxEq, Vector3, zero};
+use nalgebra::ApproxEq;
 use svo::*;
 
-impl Clone for SVO {
-    fn clone(&self) -> SVO {
-        match *self {
-            SVO::Voxel { data } => SVO::new_voxel(data),
-            SVO::Octants(ref octants) => SVO::new_octants(|ix| *octants[ix as usize].clone())
-        }
-    }
-}
-
 impl Arbitrary for SVO {
     fn arbitrary<G: Gen>(g: &mut G) -> SVO {
         fn fixed_size_arbitrary<G: Gen>(g: &mut G, size: usize) -> SVO {
@@ -30,19 +21,21 @@ impl Arbitrary for SVO {
         fixed_size_arbitrary(g, height)
     }
 
-    // fn shrink(&self) -> Box<Iterator<Item=SVO>> {
-    //     match *self {
-    //         SVO::Voxel { data, external_id } => {
-    //             Box::new((data, external_id).shrink().map(|(new_data, new_external_id)| SVO::new_voxel(new_data, new_external_id)))
-    //         },
-    //         SVO::Octants(ref octants) => Box::new(vec![
-    //             *octants[0].clone(), *octants[1].clone(),
-    //             *octants[2].clone(), *octants[3].clone(),
-    //             *octants[4].clone(), *octants[5].clone(),
-    //             *octants[6].clone(), *octants[7].clone()
-    //         ].into_iter())
-    //     }
-    // }
+    fn shrink(&self) -> Box<Iterator<Item=SVO>> {
+        match *self {
+            SVO::Voxel { data, external_id } => {
+                Box::new((data, external_id).shrink().map(|(new_data, new_external_id)| {
+                    SVO::Voxel { data: new_data, external_id: new_external_id }
+                }))
+            },
+            SVO::Octants(ref octants) => Box::new(vec![
+                *octants[0].clone(), *octants[1].clone(),
+                *octants[2].clone(), *octants[3].clone(),
+                *octants[4].clone(), *octants[5].clone(),
+                *octants[6].clone(), *octants[7].clone()
+            ].into_iter())
+        }
+    }
 }
 
 #[test]
@@ -113,14 +106,14 @@ impl SVO {
 	pub fn assert_is_voxel(&self, expected_data: VoxelData) {
 		match *self {
 			SVO::Octants(_) => panic!("Found Octants when expecting a Voxel!"),
-			SVO::Voxel{ data } => assert_eq!(data, expected_data)
+			SVO::Voxel{ data, .. } => assert_eq!(data, expected_data)
 		}
 	}
 
     pub fn assert_contains(&self, expected: Vec<(f32, f32, f32, i32, i32)>) {
-        let mut results_vec: Vec<(f32, f32, f32, i32, i32)> = Vec::new();
-        self.collect_svo(&mut results_vec, zero(), 0);
-        let results = results_vec;
+        let results: Vec<(f32, f32, f32, i32, i32)> = self.leaves().map(|leaf| {
+            (leaf.origin.x, leaf.origin.y, leaf.origin.z, leaf.depth as i32, leaf.data.voxel_type)
+        }).collect();
 
         println!("expected: {:?}", expected);
         println!("actual: {:?}", results);
@@ -138,18 +131,13 @@ impl SVO {
         }
     }
 
-    fn collect_svo(&self, results_vec: &mut Vec<(f32, f32, f32, i32, i32)>, origin: Vector3<f32>, depth: i32) {
-        match *self {
-            SVO::Voxel { data: VoxelData { voxel_type, .. } } =>
-                results_vec.push((origin.x, origin.y, origin.z, depth, voxel_type)),
-            SVO::Octants (ref octants) => {
-                for ix in 0..8 {
-                    let new_origin = origin + offset(ix, depth);
-                    octants[ix as usize].collect_svo(results_vec, new_origin, depth + 1);
-                }
-            }
-
-        }
+    // Random trees aren't always recombined, so compare them voxel by voxel down to the given depth.
+    pub fn same_voxels(&self, other: &SVO, depth: u32) -> bool {
+        let size = 1 << depth;
+        (0..size * size * size).all(|i| {
+            let coord = VoxelCoord::new(i % size, (i / size) % size, i / (size * size), depth);
+            self.get_block(coord) == other.get_block(coord)
+        })
     }
 
     pub fn floor() -> SVO {
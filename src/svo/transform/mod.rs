@@ -0,0 +1,110 @@
+use nalgebra::Vector3;
+use svo::*;
+
+#[cfg(test)]
+mod test;
+
+// A rotation or reflection of the unit cube that maps axes onto axes. Axis i of the result is
+// axis axes[i] of the original, flipped to run the other way if flips[i] is set.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct AxisTransform {
+    pub axes: [u8; 3],
+    pub flips: [bool; 3],
+}
+
+impl AxisTransform {
+    pub fn identity() -> AxisTransform {
+        AxisTransform { axes: [0, 1, 2], flips: [false; 3] }
+    }
+
+    pub fn permutation(axes: [u8; 3]) -> AxisTransform {
+        let mut sorted = axes;
+        sorted.sort();
+        assert!(sorted == [0, 1, 2], "{:?} isn't a permutation of the axes", axes);
+        AxisTransform { axes: axes, flips: [false; 3] }
+    }
+
+    // Reflect across the plane through the centre of the cube at right angles to the axis.
+    pub fn mirror(axis: u8) -> AxisTransform {
+        let mut flips = [false; 3];
+        flips[axis as usize] = true;
+        AxisTransform { axes: [0, 1, 2], flips: flips }
+    }
+
+    // Turn about the axis through the centre of the cube. Positive turns are anticlockwise when
+    // looking down the axis towards the origin, so a quarter turn about y takes +x to -z.
+    pub fn rotation(axis: u8, quarter_turns: i32) -> AxisTransform {
+        assert!(axis < 3, "There is no axis {}", axis);
+        let (u, v) = ((axis as usize + 1) % 3, (axis as usize + 2) % 3);
+        let mut quarter_turn = AxisTransform::identity();
+        quarter_turn.axes[u] = v as u8;
+        quarter_turn.flips[u] = true;
+        quarter_turn.axes[v] = u as u8;
+
+        (0..(quarter_turns % 4 + 4) % 4).fold(AxisTransform::identity(), |t, _| t.then(&quarter_turn))
+    }
+
+    // This transform followed by the next one.
+    pub fn then(&self, next: &AxisTransform) -> AxisTransform {
+        let mut combined = AxisTransform::identity();
+        for i in 0..3 {
+            let via = next.axes[i] as usize;
+            combined.axes[i] = self.axes[via];
+            combined.flips[i] = next.flips[i] != self.flips[via];
+        }
+        combined
+    }
+
+    pub fn inverse(&self) -> AxisTransform {
+        let mut inverse = AxisTransform::identity();
+        for i in 0..3 {
+            let source = self.axes[i] as usize;
+            inverse.axes[source] = i as u8;
+            inverse.flips[source] = self.flips[i];
+        }
+        inverse
+    }
+
+    // Where a point in the unit cube ends up.
+    pub fn apply(&self, point: Vector3<f32>) -> Vector3<f32> {
+        let mut moved = point;
+        for i in 0..3 {
+            let p = point[self.axes[i] as usize];
+            moved[i] = if self.flips[i] { 1. - p } else { p };
+        }
+        moved
+    }
+
+    // The octant of the original that ends up at the given octant.
+    pub fn source_octant(&self, ix: u8) -> u8 {
+        (0..3).fold(0, |source, i| {
+            let above = ((ix >> i) & 1 == 1) != self.flips[i as usize];
+            source | ((above as u8) << self.axes[i as usize])
+        })
+    }
+}
+
+impl<T: LeafData> SVO<T> {
+    // Move every octant to where the transform takes it. Nothing is split or recombined.
+    pub fn transform(&self, transform: &AxisTransform) -> SVO<T> {
+        match *self {
+            SVO::Voxel { data, .. } => SVO::new_voxel(data),
+            SVO::Octants(ref octants) => SVO::new_octants(|ix| {
+                octants[transform.source_octant(ix) as usize].transform(transform)
+            }),
+        }
+    }
+
+    pub fn rotate(&self, axis: u8, quarter_turns: i32) -> SVO<T> {
+        self.transform(&AxisTransform::rotation(axis, quarter_turns))
+    }
+
+    pub fn mirror(&self, axis: u8) -> SVO<T> {
+        self.transform(&AxisTransform::mirror(axis))
+    }
+
+    // Swap the axes around, for example [2, 1, 0] swaps x and z.
+    pub fn permute_axes(&self, axes: [u8; 3]) -> SVO<T> {
+        self.transform(&AxisTransform::permutation(axes))
+    }
+}
@@ -0,0 +1,96 @@
+use nalgebra::{Vector3, ApproxEq};
+use quickcheck::*;
+use svo::*;
+
+// Any of the 48 ways of mapping the axes onto each other.
+fn any_transform(permutation: u8, flips: u8) -> AxisTransform {
+    let axes = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]][(permutation % 6) as usize];
+    AxisTransform { axes: axes, flips: [flips & 1 != 0, flips & 2 != 0, flips & 4 != 0] }
+}
+
+#[test]
+fn quarter_turn_about_y() {
+    let t = AxisTransform::rotation(1, 1);
+    assert_approx_eq_eps!(t.apply(Vector3::new(1., 0.5, 0.5)), Vector3::new(0.5, 0.5, 0.), 0.0001);
+    assert_approx_eq_eps!(t.apply(Vector3::new(0.5, 0.5, 1.)), Vector3::new(1., 0.5, 0.5), 0.0001);
+
+    // Octant 1 is at +x, so it turns into octant 0 at -z.
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[1], VoxelData::new(1));
+    let mut expected = SVO::new_voxel(VoxelData::new(0));
+    expected.set_block(&[0], VoxelData::new(1));
+    assert_eq!(svo.rotate(1, 1), expected);
+    assert_eq!(svo.rotate(1, -3), expected);
+}
+
+#[test]
+fn mirror_and_permute() {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[1, 2], VoxelData::new(1));
+    let mut mirrored = SVO::new_voxel(VoxelData::new(0));
+    mirrored.set_block(&[0, 3], VoxelData::new(1));
+    assert_eq!(svo.mirror(0), mirrored);
+
+    let mut swapped = SVO::new_voxel(VoxelData::new(0));
+    swapped.set_block(&[2, 1], VoxelData::new(1));
+    assert_eq!(svo.permute_axes([1, 0, 2]), swapped);
+}
+
+#[test]
+#[should_panic]
+fn bad_permutation() {
+    AxisTransform::permutation([0, 0, 2]);
+}
+
+#[test]
+fn four_rotations_are_identity() {
+    fn check_four_rotations(svo: SVO, axis: u8) -> bool {
+        let axis = axis % 3;
+        svo.rotate(axis, 1).rotate(axis, 1).rotate(axis, 1).rotate(axis, 1) == svo &&
+            svo.rotate(axis, 4) == svo && svo.rotate(axis, 2) == svo.rotate(axis, -2)
+    }
+    quickcheck(check_four_rotations as fn(SVO, u8) -> bool)
+}
+
+#[test]
+fn mirror_twice_is_identity() {
+    fn check_mirror_twice(svo: SVO, axis: u8) -> bool {
+        let axis = axis % 3;
+        svo.mirror(axis).mirror(axis) == svo
+    }
+    quickcheck(check_mirror_twice as fn(SVO, u8) -> bool)
+}
+
+#[test]
+fn inverse_undoes_transform() {
+    fn check_inverse(svo: SVO, permutation: u8, flips: u8) -> bool {
+        let t = any_transform(permutation, flips);
+        svo.transform(&t).transform(&t.inverse()) == svo
+    }
+    quickcheck(check_inverse as fn(SVO, u8, u8) -> bool)
+}
+
+#[test]
+fn composition_matches_sequence() {
+    fn check_composition(svo: SVO, a: (u8, u8), b: (u8, u8)) -> bool {
+        let (a, b) = (any_transform(a.0, a.1), any_transform(b.0, b.1));
+        svo.transform(&a).transform(&b) == svo.transform(&a.then(&b))
+    }
+    quickcheck(check_composition as fn(SVO, (u8, u8), (u8, u8)) -> bool)
+}
+
+#[test]
+fn voxels_move_with_points() {
+    // The voxel at each point ends up wherever the transform takes that point.
+    fn check_voxels_move(svo: SVO, permutation: u8, flips: u8) -> bool {
+        let t = any_transform(permutation, flips);
+        let transformed = svo.transform(&t);
+        let size = 8;
+        (0..size * size * size).all(|i| {
+            let coord = VoxelCoord::new(i % size, (i / size) % size, i / (size * size), 3);
+            let centre = coord.origin() + Vector3::new(0.5, 0.5, 0.5) * coord.side_len();
+            svo.voxel_at(centre) == transformed.voxel_at(t.apply(centre))
+        })
+    }
+    quickcheck(check_voxels_move as fn(SVO, u8, u8) -> bool)
+}
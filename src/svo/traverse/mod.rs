@@ -0,0 +1,166 @@
+use nalgebra::Vector3;
+use svo::*;
+use svo::query::overlaps_box;
+
+#[cfg(test)]
+mod test;
+
+// Where a node is in the SVO's unit cube.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Position<'a> {
+    pub origin: Vector3<f32>,
+    pub side_len: f32,
+    pub index: &'a [u8],
+}
+
+impl<'a> Position<'a> {
+    pub fn depth(&self) -> u32 {
+        self.index.len() as u32
+    }
+}
+
+// Hooks for a depth-first walk over an SVO, in octant index order. See SVO::visit.
+pub trait SvoVisitor<T> {
+    // Called before a node's octants are visited. Returning false skips them, and the matching leave.
+    fn enter(&mut self, _position: &Position) -> bool { true }
+
+    // Called after all of a node's octants have been visited.
+    fn leave(&mut self, _position: &Position) {}
+
+    fn visit_leaf(&mut self, position: &Position, data: &T);
+}
+
+// Read only access to the nodes of an octree, so that the walks below, cast_ray and voxel_at are
+// written once for SVO, ArenaSVO, PersistentSVO and LodView. Nodes are cheap handles, like
+// references.
+pub trait OctreeNode<T>: Copy {
+    // The data if this node is a leaf, or None if it has octants.
+    fn leaf_data(&self) -> Option<T>;
+
+    // The octant with the given index. Only called on nodes that aren't leaves.
+    fn child(&self, ix: u8) -> Self;
+}
+
+impl<'a, T: LeafData> OctreeNode<T> for &'a SVO<T> {
+    fn leaf_data(&self) -> Option<T> {
+        self.get_voxel_data()
+    }
+
+    fn child(&self, ix: u8) -> &'a SVO<T> {
+        let svo: &'a SVO<T> = *self;
+        match *svo {
+            SVO::Octants(ref octants) => &octants[ix as usize],
+            SVO::Voxel { .. } => panic!("A voxel has no octants"),
+        }
+    }
+}
+
+// Walk any octree depth first in octant index order. See SVO::visit.
+pub fn visit_octree<T, N: OctreeNode<T>, V: SvoVisitor<T>>(root: N, visitor: &mut V) {
+    let mut index = Vec::new();
+    visit_helper(root, visitor, Vector3::new(0., 0., 0.), 1., &mut index);
+}
+
+fn visit_helper<T, N: OctreeNode<T>, V: SvoVisitor<T>>(node: N, visitor: &mut V, origin: Vector3<f32>, side_len: f32, index: &mut Vec<u8>) {
+    match node.leaf_data() {
+        Some(data) => {
+            visitor.visit_leaf(&Position { origin: origin, side_len: side_len, index: index }, &data);
+        },
+        None => {
+            if !visitor.enter(&Position { origin: origin, side_len: side_len, index: index }) { return; }
+            let child_len = side_len / 2.;
+            for ix in 0..8 {
+                index.push(ix);
+                visit_helper(node.child(ix), visitor, origin + offset_float(ix, child_len), child_len, index);
+                index.pop();
+            }
+            visitor.leave(&Position { origin: origin, side_len: side_len, index: index });
+        }
+    }
+}
+
+// A voxel found by SVO::leaves.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Leaf<'a, T: 'a> {
+    pub origin: Vector3<f32>,
+    pub side_len: f32,
+    pub depth: u32,
+    pub index: Vec<u8>,
+    pub data: &'a T,
+}
+
+impl<T: LeafData> SVO<T> {
+    pub fn visit<V: SvoVisitor<T>>(&self, visitor: &mut V) {
+        visit_octree(self, visitor);
+    }
+
+    // Every voxel in the SVO, depth first in octant index order.
+    pub fn leaves(&self) -> Leaves<T> {
+        Leaves::new(self, None)
+    }
+}
+
+pub struct Leaves<'a, T: 'a> {
+    // Nodes still to visit, with their origin, side length, depth and octant index. Their paths
+    // all share the index below: each node's path is the first depth - 1 entries followed by its
+    // octant index, since its parent was the last node at that depth to be visited.
+    stack: Vec<(&'a SVO<T>, Vector3<f32>, f32, usize, u8)>,
+    index: Vec<u8>,
+    // Subtrees entirely outside of this box are skipped.
+    bounds: Option<(Vector3<f32>, Vector3<f32>)>,
+}
+
+impl<'a, T: LeafData> Leaves<'a, T> {
+    pub fn new(svo: &'a SVO<T>, bounds: Option<(Vector3<f32>, Vector3<f32>)>) -> Leaves<'a, T> {
+        Leaves::starting_at(svo, Vector3::new(0., 0., 0.), 1., Vec::new(), bounds)
+    }
+
+    // The leaves of a subtree that's at the given position in a bigger SVO.
+    pub fn starting_at(svo: &'a SVO<T>,
+                       origin: Vector3<f32>,
+                       side_len: f32,
+                       index: Vec<u8>,
+                       bounds: Option<(Vector3<f32>, Vector3<f32>)>) -> Leaves<'a, T> {
+        // The starting node's own octant index is already at the end of its path.
+        let depth = index.len();
+        let ix = index.last().cloned().unwrap_or(0);
+        Leaves {
+            stack: vec![(svo, origin, side_len, depth, ix)],
+            index: index,
+            bounds: bounds,
+        }
+    }
+}
+
+impl<'a, T: LeafData> Iterator for Leaves<'a, T> {
+    type Item = Leaf<'a, T>;
+
+    fn next(&mut self) -> Option<Leaf<'a, T>> {
+        while let Some((svo, origin, side_len, depth, ix)) = self.stack.pop() {
+            if let Some((min, max)) = self.bounds {
+                if !overlaps_box(origin, side_len, min, max) { continue; }
+            }
+            if depth > 0 {
+                self.index.truncate(depth - 1);
+                self.index.push(ix);
+            }
+            match *svo {
+                SVO::Voxel { ref data, .. } => return Some(Leaf {
+                    origin: origin,
+                    side_len: side_len,
+                    depth: depth as u32,
+                    index: self.index.clone(),
+                    data: data,
+                }),
+                SVO::Octants(ref octants) => {
+                    // Pushed in reverse so that they come out in index order.
+                    let child_len = side_len / 2.;
+                    for ix in (0..8).rev() {
+                        self.stack.push((&octants[ix as usize], origin + offset_float(ix, child_len), child_len, depth + 1, ix));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,94 @@
+use nalgebra::{ApproxEq, Vector3};
+use quickcheck::*;
+use svo::*;
+
+#[test]
+fn leaves_of_floor() {
+    let mut svo = SVO::floor();
+    svo.set_block(&[1, 3], VoxelData::new(2));
+
+    let leaves: Vec<_> = svo.leaves().collect();
+    assert_eq!(leaves.len(), 15);
+    assert_eq!(leaves[0], Leaf {
+        origin: Vector3::new(0., 0., 0.), side_len: 0.5, depth: 1, index: vec![0], data: &VoxelData::new(1)
+    });
+    assert_eq!(leaves[4], Leaf {
+        origin: Vector3::new(0.75, 0.25, 0.), side_len: 0.25, depth: 2, index: vec![1, 3], data: &VoxelData::new(2)
+    });
+    assert_eq!(leaves[14].index, vec![7]);
+}
+
+#[test]
+fn leaves_agree_with_coords() {
+    fn check_leaves_agree_with_coords(svo: SVO) -> bool {
+        svo.leaves().all(|leaf| {
+            let coord = VoxelCoord::from_index(&leaf.index).unwrap();
+            coord.origin().approx_eq_eps(&leaf.origin, &0.0001) &&
+                coord.side_len() == leaf.side_len &&
+                coord.depth == leaf.depth &&
+                svo.get_block(coord) == Some(*leaf.data)
+        })
+    }
+    quickcheck(check_leaves_agree_with_coords as fn(SVO) -> bool)
+}
+
+// Records every hook call, and refuses to enter anything deeper than max_depth.
+struct Recorder {
+    max_depth: u32,
+    calls: Vec<(&'static str, Vec<u8>)>,
+}
+
+impl SvoVisitor<VoxelData> for Recorder {
+    fn enter(&mut self, position: &Position) -> bool {
+        self.calls.push(("enter", position.index.to_vec()));
+        position.depth() < self.max_depth
+    }
+
+    fn leave(&mut self, position: &Position) {
+        self.calls.push(("leave", position.index.to_vec()));
+    }
+
+    fn visit_leaf(&mut self, position: &Position, _data: &VoxelData) {
+        self.calls.push(("leaf", position.index.to_vec()));
+    }
+}
+
+#[test]
+fn visitor_hooks() {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[2, 5], VoxelData::new(1));
+
+    let mut recorder = Recorder { max_depth: 5, calls: vec![] };
+    svo.visit(&mut recorder);
+    let mut expected = vec![("enter", vec![]), ("leaf", vec![0]), ("leaf", vec![1]), ("enter", vec![2])];
+    for ix in 0..8 { expected.push(("leaf", vec![2, ix])); }
+    expected.push(("leave", vec![2]));
+    for ix in 3..8 { expected.push(("leaf", vec![ix])); }
+    expected.push(("leave", vec![]));
+    assert_eq!(recorder.calls, expected);
+}
+
+#[test]
+fn visitor_prunes() {
+    let mut svo = SVO::new_voxel(VoxelData::new(0));
+    svo.set_block(&[2, 5], VoxelData::new(1));
+
+    let mut recorder = Recorder { max_depth: 0, calls: vec![] };
+    svo.visit(&mut recorder);
+    assert_eq!(recorder.calls, vec![("enter", vec![])]);
+}
+
+#[test]
+fn visitor_sees_every_leaf() {
+    struct Counter(usize);
+    impl SvoVisitor<VoxelData> for Counter {
+        fn visit_leaf(&mut self, _position: &Position, _data: &VoxelData) { self.0 += 1; }
+    }
+
+    fn check_visitor_sees_every_leaf(svo: SVO) -> bool {
+        let mut counter = Counter(0);
+        svo.visit(&mut counter);
+        counter.0 == svo.leaves().count()
+    }
+    quickcheck(check_visitor_sees_every_leaf as fn(SVO) -> bool)
+}
@@ -1,4 +1,4 @@
-#[repr(C)] #[derive(Debug, PartialEq, Copy, Clone)]
+#[repr(C)] #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct VoxelData {
     pub voxel_type: i32
 }
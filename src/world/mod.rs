@@ -0,0 +1,207 @@
+use nalgebra::Vector3;
+use std::collections::HashMap;
+use std::collections::hash_map::Iter;
+use svo::{SVO, PersistentSVO, VoxelData, VoxelCoord, LeafData};
+
+mod shared;
+
+#[cfg(test)]
+mod test;
+
+pub use self::shared::SharedWorld;
+
+// The position of a chunk in the world, in units of whole chunks.
+pub type ChunkCoord = (i32, i32, i32);
+
+// A sparse grid of SVO chunks. Chunk (x, y, z) covers world space from (x, y, z) * chunk_size to
+// (x + 1, y + 1, z + 1) * chunk_size, and blocks are addressed with integer coordinates at the
+// given depth in each chunk. Missing chunks are filled with LeafData::empty.
+// Chunks are persistent SVOs shared between clones of a world, and an edit only copies the nodes
+// along the edited path, so cloning only costs as much as the chunk map.
+#[derive(Clone)]
+pub struct World<T = VoxelData> {
+    chunks: HashMap<ChunkCoord, PersistentSVO<T>>,
+    chunk_size: f32,
+    depth: u32,
+    // The smallest and largest coordinates of the loaded chunks, or None if there aren't any.
+    bounds: Option<(ChunkCoord, ChunkCoord)>,
+}
+
+impl<T: LeafData> World<T> {
+    pub fn new(chunk_size: f32, depth: u32) -> World<T> {
+        assert!(depth < 31, "World depth {} doesn't fit in i32 block coordinates", depth);
+        World { chunks: HashMap::new(), chunk_size: chunk_size, depth: depth, bounds: None }
+    }
+
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_size
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    // The world space side length of a block.
+    pub fn block_size(&self) -> f32 {
+        self.chunk_size / self.blocks_per_chunk() as f32
+    }
+
+    fn blocks_per_chunk(&self) -> i32 {
+        1 << self.depth
+    }
+
+    pub fn chunk(&self, chunk: ChunkCoord) -> Option<&PersistentSVO<T>> {
+        self.chunks.get(&chunk)
+    }
+
+    pub fn chunks(&self) -> Iter<ChunkCoord, PersistentSVO<T>> {
+        self.chunks.iter()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn insert_chunk(&mut self, chunk: ChunkCoord, svo: SVO<T>) -> Option<SVO<T>> {
+        self.include_in_bounds(chunk);
+        self.chunks.insert(chunk, PersistentSVO::from_svo(&svo)).map(|old| old.to_svo())
+    }
+
+    fn include_in_bounds(&mut self, (x, y, z): ChunkCoord) {
+        self.bounds = Some(match self.bounds {
+            None => ((x, y, z), (x, y, z)),
+            Some((lo, hi)) => ((lo.0.min(x), lo.1.min(y), lo.2.min(z)), (hi.0.max(x), hi.1.max(y), hi.2.max(z))),
+        });
+    }
+
+    // Recomputing the bounds has to look at every chunk, since the removed one may have been the
+    // only one on an edge of them.
+    fn recompute_bounds(&mut self) {
+        self.bounds = None;
+        let chunks: Vec<ChunkCoord> = self.chunks.keys().cloned().collect();
+        for chunk in chunks {
+            self.include_in_bounds(chunk);
+        }
+    }
+
+    pub fn remove_chunk(&mut self, chunk: ChunkCoord) -> Option<SVO<T>> {
+        let removed = self.chunks.remove(&chunk);
+        if removed.is_some() { self.recompute_bounds(); }
+        removed.map(|old| old.to_svo())
+    }
+
+    // The world space corner of the chunk closest to the origin.
+    pub fn chunk_origin(&self, (x, y, z): ChunkCoord) -> Vector3<f32> {
+        Vector3::new(x as f32, y as f32, z as f32) * self.chunk_size
+    }
+
+    // The chunk containing a block, and the block's coordinate inside that chunk.
+    pub fn split_block(&self, (x, y, z): (i32, i32, i32)) -> (ChunkCoord, VoxelCoord) {
+        let n = self.blocks_per_chunk();
+        let split = |f: i32| {
+            let chunk = if f < 0 { (f + 1) / n - 1 } else { f / n };
+            (chunk, (f - chunk * n) as u32)
+        };
+        let ((cx, lx), (cy, ly), (cz, lz)) = (split(x), split(y), split(z));
+        ((cx, cy, cz), VoxelCoord::new(lx, ly, lz, self.depth))
+    }
+
+    // The chunk containing a world space point, and the point in that chunk's unit cube.
+    pub fn split_point(&self, point: Vector3<f32>) -> (ChunkCoord, Vector3<f32>) {
+        let scaled = point / self.chunk_size;
+        let chunk = (scaled.x.floor() as i32, scaled.y.floor() as i32, scaled.z.floor() as i32);
+        (chunk, scaled - self.chunk_origin(chunk) / self.chunk_size)
+    }
+
+    // None if the chunk is subdivided below the block, see SVO::get_block.
+    pub fn get_block(&self, block: (i32, i32, i32)) -> Option<T> {
+        let (chunk, coord) = self.split_block(block);
+        match self.chunks.get(&chunk) {
+            Some(svo) => svo.get(&coord.to_index()),
+            None => Some(T::empty()),
+        }
+    }
+
+    // Creates the chunk if it's missing, and drops it again once it's completely empty.
+    pub fn set_block(&mut self, block: (i32, i32, i32), data: T) {
+        let (chunk, coord) = self.split_block(block);
+        let empty = T::empty();
+        let now_empty = {
+            let svo = self.chunks.entry(chunk).or_insert_with(|| PersistentSVO::new_voxel(empty));
+            *svo = svo.set_block(&coord.to_index(), data);
+            svo.get(&[]) == Some(empty)
+        };
+        if now_empty {
+            self.chunks.remove(&chunk);
+            self.recompute_bounds();
+        } else {
+            self.include_in_bounds(chunk);
+        }
+    }
+
+    // The data and depth in its chunk of the voxel containing a world space point.
+    pub fn voxel_at(&self, point: Vector3<f32>) -> (T, u32) {
+        let (chunk, local_point) = self.split_point(point);
+        match self.chunks.get(&chunk) {
+            // Rounding can leave the local point just outside of the unit cube.
+            Some(svo) => svo.voxel_at(local_point).unwrap_or((T::empty(), 0)),
+            None => (T::empty(), 0),
+        }
+    }
+
+    // Cast a ray through the chunks it passes through, nearest first, and return the world space
+    // position of the first collision with a non-empty voxel. The chunks are stepped through
+    // along the ray, and only inside of the loaded area.
+    pub fn cast_ray(&self, ray_origin: Vector3<f32>, ray_dir: Vector3<f32>) -> Option<Vector3<f32>> {
+        let (lo, hi) = get!(self.bounds);
+        guard!(ray_dir.x.is_finite() && ray_dir.y.is_finite() && ray_dir.z.is_finite());
+        guard!(ray_dir.x != 0. || ray_dir.y != 0. || ray_dir.z != 0.);
+
+        // Clip the ray to the loaded area.
+        let area_min = self.chunk_origin(lo);
+        let area_max = self.chunk_origin((hi.0 + 1, hi.1 + 1, hi.2 + 1));
+        let (mut t_enter, mut t_exit) = (0f32, ::std::f32::INFINITY);
+        for axis in 0..3 {
+            if ray_dir[axis] == 0. {
+                guard!(area_min[axis] <= ray_origin[axis] && ray_origin[axis] < area_max[axis]);
+                continue;
+            }
+            let t0 = (area_min[axis] - ray_origin[axis]) / ray_dir[axis];
+            let t1 = (area_max[axis] - ray_origin[axis]) / ray_dir[axis];
+            t_enter = t_enter.max(t0.min(t1));
+            t_exit = t_exit.min(t0.max(t1));
+        }
+        guard!(t_enter <= t_exit);
+
+        // The chunk where the ray enters, kept inside of the area in case of rounding.
+        let (entry_chunk, _) = self.split_point(ray_origin + ray_dir * t_enter);
+        let mut chunk = [entry_chunk.0.max(lo.0).min(hi.0), entry_chunk.1.max(lo.1).min(hi.1), entry_chunk.2.max(lo.2).min(hi.2)];
+
+        // 3D DDA: the ray parameter at the next chunk boundary along each axis, and between boundaries.
+        let mut step = [0; 3];
+        let mut t_next = [::std::f32::INFINITY; 3];
+        let mut t_delta = [::std::f32::INFINITY; 3];
+        for axis in 0..3 {
+            if ray_dir[axis] == 0. { continue; }
+            step[axis] = if ray_dir[axis] > 0. { 1 } else { -1 };
+            let boundary = chunk[axis] + if step[axis] > 0 { 1 } else { 0 };
+            t_next[axis] = (boundary as f32 * self.chunk_size - ray_origin[axis]) / ray_dir[axis];
+            t_delta[axis] = self.chunk_size / ray_dir[axis].abs();
+        }
+
+        loop {
+            let coord = (chunk[0], chunk[1], chunk[2]);
+            if let Some(svo) = self.chunks.get(&coord) {
+                let local_origin = (ray_origin - self.chunk_origin(coord)) / self.chunk_size;
+                if let Some(hit) = svo.cast_ray(local_origin, ray_dir) {
+                    return Some(hit * self.chunk_size + self.chunk_origin(coord));
+                }
+            }
+
+            let axis = if t_next[0] <= t_next[1] && t_next[0] <= t_next[2] { 0 } else if t_next[1] <= t_next[2] { 1 } else { 2 };
+            guard!(t_next[axis] <= t_exit);
+            chunk[axis] += step[axis];
+            t_next[axis] += t_delta[axis];
+        }
+    }
+}
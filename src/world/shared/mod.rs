@@ -0,0 +1,79 @@
+use nalgebra::Vector3;
+use std::sync::{Arc, Mutex, RwLock};
+use svo::{VoxelData, LeafData};
+use world::World;
+
+#[cfg(test)]
+mod test;
+
+// A handle to a world that any number of threads can read while one at a time applies edits.
+// Readers get an immutable snapshot of the latest version, which edits never change. Each batch
+// of edits is made to a copy of the world, so only the chunks it touches are copied, and the
+// copy becomes the latest version once the whole batch is done.
+pub struct SharedWorld<T = VoxelData> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    latest: RwLock<Version<T>>,
+    // Held for the whole of a batch so that concurrent batches don't lose each other's edits.
+    writer: Mutex<()>,
+}
+
+struct Version<T> {
+    world: Arc<World<T>>,
+    number: u64,
+}
+
+impl<T> Clone for SharedWorld<T> {
+    fn clone(&self) -> SharedWorld<T> {
+        SharedWorld { inner: self.inner.clone() }
+    }
+}
+
+impl<T: LeafData> SharedWorld<T> {
+    pub fn new(world: World<T>) -> SharedWorld<T> {
+        SharedWorld {
+            inner: Arc::new(Inner {
+                latest: RwLock::new(Version { world: Arc::new(world), number: 0 }),
+                writer: Mutex::new(()),
+            }),
+        }
+    }
+
+    // The latest version of the world. It stays the same however long it's kept.
+    pub fn snapshot(&self) -> Arc<World<T>> {
+        self.inner.latest.read().unwrap_or_else(|e| e.into_inner()).world.clone()
+    }
+
+    // How many batches have been applied, which changes whenever the snapshot would.
+    pub fn version(&self) -> u64 {
+        self.inner.latest.read().unwrap_or_else(|e| e.into_inner()).number
+    }
+
+    // Apply a batch of edits. Readers see either none of them or all of them. A batch that panics
+    // is dropped without publishing anything, so the next batch can carry on from the last version.
+    pub fn edit<F, R>(&self, edits: F) -> R
+            where F: FnOnce(&mut World<T>) -> R {
+        let _writer = self.inner.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let mut world = (*self.snapshot()).clone();
+        let result = edits(&mut world);
+
+        let mut latest = self.inner.latest.write().unwrap_or_else(|e| e.into_inner());
+        latest.world = Arc::new(world);
+        latest.number += 1;
+        result
+    }
+
+    pub fn set_block(&self, block: (i32, i32, i32), data: T) {
+        self.edit(|world| world.set_block(block, data));
+    }
+
+    pub fn get_block(&self, block: (i32, i32, i32)) -> Option<T> {
+        self.snapshot().get_block(block)
+    }
+
+    pub fn cast_ray(&self, ray_origin: Vector3<f32>, ray_dir: Vector3<f32>) -> Option<Vector3<f32>> {
+        self.snapshot().cast_ray(ray_origin, ray_dir)
+    }
+}
@@ -0,0 +1,103 @@
+use nalgebra::{Vector3, ApproxEq};
+use std::sync::Arc;
+use std::thread;
+use svo::*;
+use world::*;
+
+fn shared_world() -> SharedWorld {
+    SharedWorld::new(World::new(2.0, 2))
+}
+
+#[test]
+fn snapshots_dont_see_later_edits() {
+    let world = shared_world();
+    world.set_block((1, 1, 1), VoxelData::new(1));
+    let snapshot = world.snapshot();
+    world.set_block((1, 1, 1), VoxelData::new(2));
+    world.set_block((5, 0, 0), VoxelData::new(1));
+
+    assert_eq!(snapshot.get_block((1, 1, 1)), Some(VoxelData::new(1)));
+    assert_eq!(snapshot.chunk_count(), 1);
+    assert_eq!(world.get_block((1, 1, 1)), Some(VoxelData::new(2)));
+    assert_eq!(world.snapshot().chunk_count(), 2);
+    assert_eq!(world.version(), 3);
+}
+
+#[test]
+fn unedited_chunks_are_shared() {
+    let world = shared_world();
+    world.edit(|world| {
+        world.set_block((0, 0, 0), VoxelData::new(1));
+        world.set_block((4, 0, 0), VoxelData::new(1));
+    });
+    let before = world.snapshot();
+    world.set_block((5, 0, 0), VoxelData::new(1));
+    let after = world.snapshot();
+
+    let same_chunk = |coord| before.chunk(coord).unwrap().same_root(after.chunk(coord).unwrap());
+    assert!(same_chunk((0, 0, 0)));
+    assert!(!same_chunk((1, 0, 0)));
+}
+
+#[test]
+fn panicking_batch_doesnt_poison_the_world() {
+    let world = shared_world();
+    world.set_block((1, 1, 1), VoxelData::new(1));
+    let panicked = {
+        let world = world.clone();
+        thread::spawn(move || world.edit(|world| {
+            world.set_block((1, 1, 1), VoxelData::new(2));
+            panic!("Edit failed halfway through");
+        })).join()
+    };
+    assert!(panicked.is_err());
+
+    // The failed batch wasn't published, and later batches still go through.
+    assert_eq!(world.get_block((1, 1, 1)), Some(VoxelData::new(1)));
+    assert_eq!(world.version(), 1);
+    world.set_block((2, 1, 1), VoxelData::new(3));
+    assert_eq!(world.get_block((2, 1, 1)), Some(VoxelData::new(3)));
+    assert_eq!(world.version(), 2);
+}
+
+#[test]
+fn readers_see_whole_batches() {
+    let world = shared_world();
+    let writer = {
+        let world = world.clone();
+        thread::spawn(move || for i in 1..50 {
+            // Every batch fills the same blocks with the same data.
+            world.edit(|world| for x in 0..8 {
+                world.set_block((x, 0, 0), VoxelData::new(i));
+            });
+        })
+    };
+
+    let readers: Vec<_> = (0..4).map(|_| {
+        let world = world.clone();
+        thread::spawn(move || for _ in 0..50 {
+            let snapshot = world.snapshot();
+            let first = snapshot.get_block((0, 0, 0));
+            for x in 1..8 {
+                assert_eq!(snapshot.get_block((x, 0, 0)), first);
+            }
+        })
+    }).collect();
+
+    writer.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert_eq!(world.get_block((7, 0, 0)), Some(VoxelData::new(49)));
+}
+
+#[test]
+fn raycast_from_another_thread() {
+    let world = shared_world();
+    world.set_block((9, 1, 1), VoxelData::new(1));
+    let snapshot: Arc<World> = world.snapshot();
+    let hit = thread::spawn(move || {
+        snapshot.cast_ray(Vector3::new(-3.0, 0.75, 0.75), Vector3::new(1.0, 0.0, 0.0))
+    }).join().unwrap();
+    assert_approx_eq_eps!(hit.unwrap(), Vector3::new(4.5, 0.75, 0.75), 0.01);
+}
@@ -0,0 +1,100 @@
+use nalgebra::{Vector3, ApproxEq};
+use svo::*;
+use world::*;
+
+fn world() -> World {
+    World::new(2.0, 2)
+}
+
+#[test]
+fn split_negative_blocks() {
+    let world = world();
+    assert_eq!(world.split_block((0, 3, 4)), ((0, 0, 1), VoxelCoord::new(0, 3, 0, 2)));
+    assert_eq!(world.split_block((-1, -4, -5)), ((-1, -1, -2), VoxelCoord::new(3, 0, 3, 2)));
+    assert_eq!(world.split_point(Vector3::new(-1.0, 3.0, 0.5)),
+               ((-1, 1, 0), Vector3::new(0.5, 0.5, 0.25)));
+}
+
+#[test]
+fn set_and_get_across_chunks() {
+    let mut world = world();
+    assert_eq!(world.get_block((100, -100, 7)), Some(VoxelData::new(0)));
+
+    world.set_block((3, 0, 0), VoxelData::new(1));
+    world.set_block((4, 0, 0), VoxelData::new(2));
+    world.set_block((-1, 0, 0), VoxelData::new(3));
+    assert_eq!(world.chunk_count(), 3);
+    assert_eq!(world.get_block((3, 0, 0)), Some(VoxelData::new(1)));
+    assert_eq!(world.get_block((4, 0, 0)), Some(VoxelData::new(2)));
+    assert_eq!(world.get_block((-1, 0, 0)), Some(VoxelData::new(3)));
+    assert_eq!(world.get_block((2, 0, 0)), Some(VoxelData::new(0)));
+
+    // Each block is a quarter of a chunk, so half a world space unit.
+    assert_eq!(world.voxel_at(Vector3::new(2.2, 0.1, 0.1)), (VoxelData::new(2), 2));
+    assert_eq!(world.voxel_at(Vector3::new(-0.2, 0.1, 0.1)), (VoxelData::new(3), 2));
+
+    world.set_block((4, 0, 0), VoxelData::new(0));
+    assert_eq!(world.chunk_count(), 2);
+    assert_eq!(world.chunk((1, 0, 0)), None);
+}
+
+#[test]
+fn cast_ray_across_chunks() {
+    let mut world = world();
+    world.set_block((9, 1, 1), VoxelData::new(1));
+    world.set_block((1, 1, 1), VoxelData::new(0));
+    world.insert_chunk((0, 0, 0), SVO::new_voxel(VoxelData::new(0)));
+
+    let hit = world.cast_ray(Vector3::new(-3.0, 0.75, 0.75), Vector3::new(1.0, 0.0, 0.0));
+    assert_approx_eq_eps!(hit.unwrap(), Vector3::new(4.5, 0.75, 0.75), 0.01);
+
+    // The nearer chunk wins even when it's further from the world origin.
+    world.set_block((-6, 1, 1), VoxelData::new(2));
+    let hit = world.cast_ray(Vector3::new(-4.0, 0.75, 0.75), Vector3::new(1.0, 0.0, 0.0));
+    assert_approx_eq_eps!(hit.unwrap(), Vector3::new(-3.0, 0.75, 0.75), 0.01);
+
+    assert_eq!(world.cast_ray(Vector3::new(-3.0, 3.0, 0.75), Vector3::new(1.0, 0.0, 0.0)), None);
+}
+
+#[test]
+fn cast_ray_degenerate_directions() {
+    let mut world = world();
+    world.set_block((1, 1, 1), VoxelData::new(1));
+    let origin = Vector3::new(-3.0, 0.75, 0.75);
+    assert_eq!(world.cast_ray(origin, Vector3::new(0.0, 0.0, 0.0)), None);
+    assert_eq!(world.cast_ray(origin, Vector3::new(::std::f32::NAN, 1.0, 0.0)), None);
+    assert_eq!(World::<VoxelData>::new(2.0, 2).cast_ray(origin, Vector3::new(1.0, 0.0, 0.0)), None);
+}
+
+#[test]
+fn cast_ray_steps_through_chunks() {
+    // A diagonal ray through empty chunks that hits a block several chunks away.
+    let mut world = world();
+    world.set_block((0, 0, 0), VoxelData::new(0));
+    world.set_block((13, 9, 1), VoxelData::new(1));
+    world.insert_chunk((1, 1, 0), SVO::new_voxel(VoxelData::new(0)));
+    world.insert_chunk((2, 1, 0), SVO::new_voxel(VoxelData::new(0)));
+
+    let hit = world.cast_ray(Vector3::new(0.25, 0.25, 0.75), Vector3::new(1.0, 0.7, 0.0));
+    assert_approx_eq_eps!(hit.unwrap(), Vector3::new(6.5, 4.625, 0.75), 0.01);
+
+    // Starting beyond the block and pointing away misses.
+    assert_eq!(world.cast_ray(Vector3::new(8.0, 0.25, 0.75), Vector3::new(1.0, 0.0, 0.0)), None);
+}
+
+#[test]
+fn bounds_only_cover_loaded_chunks() {
+    let mut world = world();
+    // Clearing a block far away makes a chunk that's dropped straight away.
+    world.set_block((400, 0, 0), VoxelData::new(0));
+    assert_eq!(world.bounds, None);
+
+    world.set_block((0, 0, 0), VoxelData::new(1));
+    world.set_block((-9, 5, 0), VoxelData::new(1));
+    assert_eq!(world.bounds, Some(((-3, 0, 0), (0, 1, 0))));
+
+    world.set_block((-9, 5, 0), VoxelData::new(0));
+    assert_eq!(world.bounds, Some(((0, 0, 0), (0, 0, 0))));
+    world.remove_chunk((0, 0, 0));
+    assert_eq!(world.bounds, None);
+}